@@ -0,0 +1,249 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+use wipple::*;
+use wipple_projects::*;
+
+/// Generate documentation for a Wipple project
+#[derive(StructOpt)]
+pub struct Doc {
+    /// Generate Markdown instead of HTML
+    #[structopt(long)]
+    pub markdown: bool,
+
+    /// Folder to write the documentation to
+    #[structopt(short, long, default_value = "docs")]
+    pub output: PathBuf,
+
+    /// Path to the project or file (defaults to the current folder)
+    pub path: Option<PathBuf>,
+}
+
+/// A variable in the documented module.
+struct Item {
+    name: String,
+    description: String,
+    documentation: Option<String>,
+}
+
+impl Doc {
+    pub fn run(&self) -> wipple::Result<()> {
+        wipple::setup();
+        wipple_projects::setup();
+
+        // The program is evaluated to find its variables, but shouldn't print
+        // anything while doing so
+        *Environment::global().borrow_mut().show() = ShowFn::new(|_, _, _| Ok(()));
+
+        let stack = Stack::new();
+
+        let path = self
+            .path
+            .clone()
+            .unwrap_or_else(|| std::env::current_dir().unwrap());
+
+        let module = if path.is_dir() {
+            load_project(&path.join("project.wpl"), &stack)?
+        } else {
+            import_path(&path, &stack)?
+        };
+
+        let title = path
+            .canonicalize()
+            .ok()
+            .as_deref()
+            .and_then(Path::file_stem)
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| String::from("Documentation"));
+
+        let items = items(&module, &stack)?;
+
+        self.write(&title, &items).map_err(|error| {
+            ReturnState::Error(Box::new(wipple::Error::new(
+                &format!(
+                    "Error writing documentation to {}: {}",
+                    self.output.to_string_lossy(),
+                    error
+                ),
+                &stack,
+            )))
+        })
+    }
+
+    fn write(&self, title: &str, items: &[Item]) -> io::Result<()> {
+        let extension = if self.markdown { "md" } else { "html" };
+
+        let variables_folder = self.output.join("variables");
+        fs::create_dir_all(&variables_folder)?;
+
+        let index = if self.markdown {
+            markdown_index(title, items, extension)
+        } else {
+            html_index(title, items, extension)
+        };
+
+        fs::write(self.output.join(format!("index.{}", extension)), index)?;
+
+        for item in items {
+            let page = if self.markdown {
+                markdown_page(item)
+            } else {
+                html_page(title, item, extension)
+            };
+
+            fs::write(
+                variables_folder.join(format!("{}.{}", file_name(&item.name), extension)),
+                page,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Collect the variables in a module, sorted by name.
+fn items(module: &Module, stack: &Stack) -> wipple::Result<Vec<Item>> {
+    let mut variables = module
+        .env
+        .borrow_mut()
+        .variables()
+        .clone()
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    variables.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    variables
+        .into_iter()
+        .map(|(name, value)| {
+            let documentation = value
+                .get_primitive_if_present::<Documentation>(&module.env, stack)?
+                .map(|documentation| documentation.text);
+
+            Ok(Item {
+                description: format!("{} : {}", name, value.try_format(&module.env, stack)),
+                name,
+                documentation,
+            })
+        })
+        .collect()
+}
+
+/// Variable names can contain characters that aren't allowed in file names, so
+/// they're replaced with their character codes.
+fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c.to_string()
+            } else {
+                format!("_{:x}", c as u32)
+            }
+        })
+        .collect()
+}
+
+fn summary(item: &Item) -> Option<&str> {
+    item.documentation
+        .as_deref()
+        .and_then(|documentation| documentation.lines().next())
+        .filter(|line| !line.trim().is_empty())
+}
+
+fn markdown_index(title: &str, items: &[Item], extension: &str) -> String {
+    let mut output = format!("# {}\n\n", title);
+
+    for item in items {
+        output.push_str(&format!(
+            "- [`{}`](variables/{}.{})",
+            item.name,
+            file_name(&item.name),
+            extension
+        ));
+
+        if let Some(summary) = summary(item) {
+            output.push_str(&format!(" — {}", summary));
+        }
+
+        output.push('\n');
+    }
+
+    output
+}
+
+fn markdown_page(item: &Item) -> String {
+    let mut output = format!(
+        "# `{}`\n\n```wipple\n{}\n```\n",
+        item.name, item.description
+    );
+
+    // Documentation is written in Markdown already
+    if let Some(documentation) = &item.documentation {
+        output.push('\n');
+        output.push_str(documentation);
+        output.push('\n');
+    }
+
+    output
+}
+
+fn html_index(title: &str, items: &[Item], extension: &str) -> String {
+    let mut body = format!("<h1>{}</h1>\n<ul>\n", escape_html(title));
+
+    for item in items {
+        body.push_str(&format!(
+            "<li><a href=\"variables/{}.{}\"><code>{}</code></a>",
+            file_name(&item.name),
+            extension,
+            escape_html(&item.name)
+        ));
+
+        if let Some(summary) = summary(item) {
+            body.push_str(&format!(" — {}", escape_html(summary)));
+        }
+
+        body.push_str("</li>\n");
+    }
+
+    body.push_str("</ul>\n");
+
+    html_document(title, &body)
+}
+
+fn html_page(title: &str, item: &Item, extension: &str) -> String {
+    let mut body = format!(
+        "<p><a href=\"../index.{}\">{}</a></p>\n<h1><code>{}</code></h1>\n<pre><code>{}</code></pre>\n",
+        extension,
+        escape_html(title),
+        escape_html(&item.name),
+        escape_html(&item.description)
+    );
+
+    // Blank lines separate paragraphs
+    if let Some(documentation) = &item.documentation {
+        for paragraph in documentation.split("\n\n") {
+            if !paragraph.trim().is_empty() {
+                body.push_str(&format!("<p>{}</p>\n", escape_html(paragraph.trim())));
+            }
+        }
+    }
+
+    html_document(&format!("{} — {}", item.name, title), &body)
+}
+
+fn html_document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(title),
+        body
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,124 @@
+use colored::Colorize;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+use wipple::*;
+use wipple_parser::*;
+
+/// Format Wipple files
+#[derive(StructOpt)]
+pub struct Fmt {
+    /// Report files that aren't formatted instead of formatting them
+    #[structopt(long)]
+    pub check: bool,
+
+    /// Files or folders to format (defaults to the current folder)
+    pub paths: Vec<PathBuf>,
+}
+
+impl Fmt {
+    pub fn run(&self) -> wipple::Result<()> {
+        let stack = Stack::new();
+
+        let io_error = |path: &Path, error: io::Error| {
+            ReturnState::Error(Box::new(wipple::Error::new(
+                &format!("Error formatting {}: {}", path.to_string_lossy(), error),
+                &stack,
+            )))
+        };
+
+        let paths = if self.paths.is_empty() {
+            vec![std::env::current_dir().unwrap()]
+        } else {
+            self.paths.clone()
+        };
+
+        let mut files = Vec::new();
+        for path in &paths {
+            collect_files(path, &mut files).map_err(|error| io_error(path, error))?;
+        }
+
+        let mut unformatted = 0;
+        let mut failed = 0;
+
+        for file in files {
+            let code = fs::read_to_string(&file).map_err(|error| io_error(&file, error))?;
+
+            let formatted = match format_file(&code) {
+                Ok(formatted) => formatted,
+                Err(error) => {
+                    eprintln!("{}\n", error.render(&code, Some(&file)).red());
+                    failed += 1;
+                    continue;
+                }
+            };
+
+            if formatted == code {
+                continue;
+            }
+
+            if self.check {
+                println!("{} is not formatted", file.to_string_lossy());
+                unformatted += 1;
+            } else {
+                fs::write(&file, formatted).map_err(|error| io_error(&file, error))?;
+            }
+        }
+
+        if failed > 0 {
+            return Err(ReturnState::Error(Box::new(wipple::Error::new(
+                &format!("{} file(s) could not be formatted", failed),
+                &stack,
+            ))));
+        }
+
+        if unformatted > 0 {
+            return Err(ReturnState::Error(Box::new(wipple::Error::new(
+                &format!("{} file(s) are not formatted", unformatted),
+                &stack,
+            ))));
+        }
+
+        Ok(())
+    }
+}
+
+/// Find the Wipple files in a folder, skipping hidden files like installed
+/// dependencies.
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+
+    let mut entries = path
+        .read_dir()?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    entries.sort();
+
+    for entry in entries {
+        if entry
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with('.')
+        {
+            continue;
+        }
+
+        if entry.is_dir() {
+            collect_files(&entry, files)?;
+        } else if entry
+            .extension()
+            .is_some_and(|extension| extension == "wpl")
+        {
+            files.push(entry);
+        }
+    }
+
+    Ok(())
+}
@@ -1,34 +1,82 @@
+mod doc;
+mod fmt;
+mod repl;
 mod run;
 
 use colored::Colorize;
+use doc::*;
+use fmt::*;
+use repl::*;
 use run::*;
-use std::process::exit;
+use std::{process::exit, str::FromStr};
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
 #[structopt(name = "The Wipple interpreter", bin_name = "wipple", no_version)]
-pub enum Args {
+pub struct Args {
+    /// How to print errors: 'human' or 'json'
+    #[structopt(long, global = true, default_value = "human")]
+    pub error_format: ErrorFormat,
+
+    #[structopt(subcommand)]
+    pub command: Command,
+}
+
+#[derive(StructOpt)]
+pub enum Command {
     Run(Run),
+    Repl(Repl),
+    Fmt(Fmt),
+    Doc(Doc),
+}
+
+#[derive(Clone, Copy)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+impl FromStr for ErrorFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(ErrorFormat::Human),
+            "json" => Ok(ErrorFormat::Json),
+            _ => Err(format!("Unknown error format '{}'", s)),
+        }
+    }
 }
 
 fn main() {
-    exit(run())
+    // Programs run on a larger stack than the main thread's so they can
+    // recurse more deeply
+    exit(wipple::run_on_large_stack(run))
 }
 
 fn run() -> i32 {
     let args = Args::from_args();
 
-    match args {
-        Args::Run(run) => {
-            if let Err(state) = run.run() {
-                eprintln!(
-                    "{}",
-                    state.into_error(&wipple::Stack::new()).to_string().red()
-                );
-                return 1;
-            }
-        }
+    let result = match args.command {
+        Command::Run(run) => run.run(),
+        Command::Repl(repl) => repl.run(args.error_format),
+        Command::Fmt(fmt) => fmt.run(),
+        Command::Doc(doc) => doc.run(),
+    };
+
+    if let Err(state) = result {
+        report_error(&state.into_error(&wipple::Stack::new()), args.error_format);
+        return 1;
     }
 
     0
 }
+
+/// Print an error to standard error in the format chosen with
+/// '--error-format'.
+pub fn report_error(error: &wipple::Error, error_format: ErrorFormat) {
+    match error_format {
+        ErrorFormat::Human => eprintln!("{}", error.to_string().red()),
+        ErrorFormat::Json => eprintln!("{}", error.to_json()),
+    }
+}
@@ -0,0 +1,228 @@
+use crate::{report_error, ErrorFormat};
+use colored::Colorize;
+use rustyline::{error::ReadlineError, Editor};
+use std::path::Path;
+use structopt::StructOpt;
+use wipple::*;
+use wipple_parser::*;
+use wipple_projects::*;
+
+fn setup() {
+    wipple::setup();
+    wipple_projects::setup();
+    crate::run::setup();
+}
+
+/// Start an interactive session
+#[derive(StructOpt)]
+pub struct Repl {}
+
+impl Repl {
+    pub fn run(&self, error_format: ErrorFormat) -> wipple::Result<()> {
+        setup();
+
+        let mut session = Session::new(error_format);
+        let mut editor = Editor::<()>::new();
+        let mut input = String::new();
+
+        println!("{}", "Type :help for a list of commands".bright_black());
+
+        loop {
+            let prompt = if input.is_empty() {
+                "wipple> "
+            } else {
+                "   ...> "
+            };
+
+            let line = match editor.readline(prompt) {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) => {
+                    // Discard any partially entered input
+                    input.clear();
+                    continue;
+                }
+                Err(ReadlineError::Eof) => break,
+                Err(error) => {
+                    return Err(ReturnState::Error(Box::new(wipple::Error::new(
+                        &format!("Error reading input: {}", error),
+                        &session.stack,
+                    ))))
+                }
+            };
+
+            if input.is_empty() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                if let Some(command) = Command::parse(&line) {
+                    editor.add_history_entry(line.as_str());
+
+                    match command {
+                        Command::Quit => break,
+                        command => session.run_command(command),
+                    }
+
+                    continue;
+                }
+            }
+
+            input.push_str(&line);
+            input.push('\n');
+
+            // Keep reading lines until every bracket is closed
+            if !unclosed_brackets(&input).is_empty() {
+                continue;
+            }
+
+            editor.add_history_entry(input.trim_end());
+            session.evaluate(&input);
+            input.clear();
+        }
+
+        Ok(())
+    }
+}
+
+enum Command<'a> {
+    Help,
+    Env,
+    Reset,
+    Load(&'a str),
+    Quit,
+    Unknown(&'a str),
+}
+
+impl<'a> Command<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        let line = line.trim();
+
+        // Lines like ': x' are Wipple code, not commands
+        let command = line.strip_prefix(':')?;
+        if !command.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return None;
+        }
+
+        let (name, argument) = match command.find(char::is_whitespace) {
+            Some(index) => (&command[..index], command[index..].trim()),
+            None => (command, ""),
+        };
+
+        Some(match name {
+            "help" => Command::Help,
+            "env" => Command::Env,
+            "reset" => Command::Reset,
+            "load" => Command::Load(argument),
+            "quit" | "exit" => Command::Quit,
+            _ => Command::Unknown(name),
+        })
+    }
+}
+
+struct Session {
+    env: EnvironmentRef,
+    stack: Stack,
+    error_format: ErrorFormat,
+}
+
+impl Session {
+    fn new(error_format: ErrorFormat) -> Self {
+        let mut stack = Stack::new();
+        stack.project_root = std::env::current_dir().ok();
+
+        Session {
+            env: Environment::child_of(&Environment::global()).into_ref(),
+            stack,
+            error_format,
+        }
+    }
+
+    fn evaluate(&self, code: &str) {
+        let (ast, errors) = parse_inline_program_recovering(code);
+
+        if !errors.is_empty() {
+            self.report_error(
+                wipple::Error::new(&render_errors(&errors, code, None), &self.stack)
+                    .with_code(ErrorCode::SyntaxError),
+            );
+
+            return;
+        }
+
+        let program = convert(&ast, None);
+
+        match program.evaluate(&self.env, &self.stack) {
+            Ok(result) => {
+                if !result.is_empty() {
+                    println!("{}", result.try_format(&self.env, &self.stack));
+                }
+            }
+            Err(state) => self.report(state),
+        }
+    }
+
+    fn run_command(&mut self, command: Command) {
+        match command {
+            Command::Help => {
+                println!(":env          List the variables defined in this session");
+                println!(":reset        Remove all variables and imported modules");
+                println!(":load <file>  Evaluate a file and use its variables");
+                println!(":quit         Exit the session");
+            }
+            Command::Env => {
+                let mut variables = self
+                    .env
+                    .borrow_mut()
+                    .variables()
+                    .clone()
+                    .into_iter()
+                    .collect::<Vec<_>>();
+
+                variables.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+                for (name, value) in variables {
+                    println!("{} : {}", name, value.try_format(&self.env, &self.stack));
+                }
+            }
+            Command::Reset => {
+                // Start again from a new global environment, so the session
+                // behaves like a new one
+                setup();
+                *self = Session::new(self.error_format);
+            }
+            Command::Load(path) => {
+                if path.is_empty() {
+                    self.report_error(wipple::Error::new("Expected a path to a file", &self.stack));
+                    return;
+                }
+
+                // Evaluate the file again in case it changed since it was last
+                // imported
+                let path = Path::new(path);
+                invalidate_module(path);
+
+                match import_path(path, &self.stack) {
+                    Ok(module) => self.env.borrow_mut().r#use(&module.env.borrow()),
+                    Err(state) => self.report(state),
+                }
+            }
+            Command::Quit => unreachable!("Handled by the caller"),
+            Command::Unknown(name) => {
+                self.report_error(
+                    wipple::Error::new(&format!("Unknown command ':{}'", name), &self.stack)
+                        .with_help("Type :help for a list of commands"),
+                );
+            }
+        }
+    }
+
+    fn report(&self, state: ReturnState) {
+        self.report_error(state.into_error(&self.stack));
+    }
+
+    /// Errors are printed like the errors from 'wipple run', so they use the
+    /// same '--error-format'.
+    fn report_error(&self, error: wipple::Error) {
+        report_error(&error, self.error_format);
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 use structopt::StructOpt;
 use wipple::*;
@@ -12,6 +12,23 @@ pub struct Run {
     #[structopt(name = "code", short = "e")]
     pub evaluate_string: Option<String>,
 
+    /// Maximum number of nested calls before the program is stopped
+    #[structopt(long)]
+    pub max_depth: Option<usize>,
+
+    /// Maximum number of evaluations and calls before the program is stopped
+    #[structopt(long)]
+    pub max_steps: Option<usize>,
+
+    /// Number of seconds the program can run for before it's stopped
+    #[structopt(long)]
+    pub timeout: Option<f64>,
+
+    /// Approximate number of bytes the program can hold at once before it's
+    /// stopped
+    #[structopt(long)]
+    pub max_allocated: Option<usize>,
+
     /// Path to the program
     pub path: Option<PathBuf>,
 }
@@ -23,16 +40,33 @@ impl Run {
         setup();
 
         let env = Environment::global();
-        let stack = Stack::new();
+        let mut stack = Stack::new();
+        if let Some(max_depth) = self.max_depth {
+            stack.max_depth = max_depth;
+        }
+
+        if let Some(max_steps) = self.max_steps {
+            stack.budget = stack.budget.with_max_steps(max_steps);
+        }
+
+        if let Some(timeout) = self.timeout {
+            stack.budget = stack.budget.with_timeout(Duration::from_secs_f64(timeout));
+        }
+
+        if let Some(max_allocated) = self.max_allocated {
+            stack.budget = stack.budget.with_max_allocated(max_allocated);
+        }
 
         match &self.evaluate_string {
             Some(code) => {
-                let ast = parse_inline_program(&code).map_err(|error| {
-                    wipple::ReturnState::Error(wipple::Error::new(
-                        &format!("Error parsing: {}", error.message),
-                        &stack,
-                    ))
-                })?;
+                let (ast, errors) = parse_inline_program_recovering(code);
+
+                if !errors.is_empty() {
+                    return Err(wipple::ReturnState::Error(Box::new(
+                        wipple::Error::new(&render_errors(&errors, code, None), &stack)
+                            .with_code(ErrorCode::SyntaxError),
+                    )));
+                }
 
                 let program = convert(&ast, None);
 
@@ -57,7 +91,7 @@ impl Run {
     }
 }
 
-fn setup() {
+pub(crate) fn setup() {
     *Environment::global().borrow_mut().show() = ShowFn::new(move |value, env, stack| {
         println!("{}", value.evaluate(env, stack)?.format(env, stack)?);
 
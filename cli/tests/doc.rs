@@ -0,0 +1,176 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+const LIBRARY: &str = r#"--- Adds one
+---
+--- Works with any number.
+increment : x -> x + 1
+
+inc : increment
+
+empty? : x -> x
+
+../up : 1
+
+--- Compare <a> & "b"
+n : 42
+"#;
+
+/// Document a file containing 'LIBRARY', returning the output folder.
+fn doc(name: &str, options: &[&str]) -> PathBuf {
+    let folder =
+        std::env::temp_dir().join(format!("wipple-doc-test-{}-{}", name, std::process::id()));
+
+    if folder.exists() {
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    fs::create_dir_all(&folder).unwrap();
+    fs::write(folder.join("lib.wpl"), LIBRARY).unwrap();
+
+    let output_folder = folder.join("docs");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_wipple_cli"))
+        .arg("doc")
+        .args(options)
+        .arg("--output")
+        .arg(&output_folder)
+        .arg(folder.join("lib.wpl"))
+        .output()
+        .expect("failed to run wipple");
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output_folder
+}
+
+fn read(path: &Path) -> String {
+    fs::read_to_string(path).unwrap_or_else(|error| panic!("{}: {}", path.display(), error))
+}
+
+fn files(folder: &Path) -> Vec<String> {
+    let mut files = fs::read_dir(folder)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+
+    files.sort();
+    files
+}
+
+#[test]
+fn markdown() {
+    let output = doc("markdown", &["--markdown"]);
+
+    assert_eq!(
+        read(&output.join("index.md")),
+        "# lib
+
+- [`../up`](variables/_2e_2e_2fup.md)
+- [`empty?`](variables/empty_3f.md)
+- [`inc`](variables/inc.md) — Adds one
+- [`increment`](variables/increment.md) — Adds one
+- [`n`](variables/n.md) — Compare <a> & \"b\"
+"
+    );
+
+    assert_eq!(
+        files(&output.join("variables")),
+        [
+            "_2e_2e_2fup.md",
+            "empty_3f.md",
+            "inc.md",
+            "increment.md",
+            "n.md"
+        ]
+    );
+
+    assert_eq!(
+        read(&output.join("variables/increment.md")),
+        "# `increment`
+
+```wipple
+increment : <closure 'increment'>
+```
+
+Adds one
+
+Works with any number.
+"
+    );
+
+    fs::remove_dir_all(output.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn html() {
+    let output = doc("html", &[]);
+
+    assert_eq!(
+        files(&output.join("variables")),
+        [
+            "_2e_2e_2fup.html",
+            "empty_3f.html",
+            "inc.html",
+            "increment.html",
+            "n.html"
+        ]
+    );
+
+    let index = read(&output.join("index.html"));
+    assert!(
+        index.contains("<li><a href=\"variables/empty_3f.html\"><code>empty?</code></a></li>\n")
+    );
+    assert!(index.contains(
+        "<li><a href=\"variables/n.html\"><code>n</code></a> — Compare &lt;a&gt; &amp; &quot;b&quot;</li>\n"
+    ));
+
+    assert_eq!(
+        read(&output.join("variables/increment.html")),
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>increment — lib</title>
+</head>
+<body>
+<p><a href=\"../index.html\">lib</a></p>
+<h1><code>increment</code></h1>
+<pre><code>increment : &lt;closure 'increment'&gt;</code></pre>
+<p>Adds one</p>
+<p>Works with any number.</p>
+</body>
+</html>
+"
+    );
+
+    fs::remove_dir_all(output.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn assigned_variables_inherit_documentation() {
+    let output = doc("inherit", &["--markdown"]);
+
+    assert_eq!(
+        read(&output.join("variables/inc.md")),
+        "# `inc`
+
+```wipple
+inc : <closure 'increment'>
+```
+
+Adds one
+
+Works with any number.
+"
+    );
+
+    fs::remove_dir_all(output.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,64 @@
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Output},
+};
+
+fn fmt(options: &[&str], folder: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_wipple_cli"))
+        .arg("fmt")
+        .args(options)
+        .arg(folder)
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("failed to run wipple")
+}
+
+const UNFORMATTED: &str = "x   :  1 -- one\nf : x ->    [\n  show  x\n]\n";
+const FORMATTED: &str = "x : 1 -- one\nf : x -> [\n    show x\n]\n";
+
+#[test]
+fn check_reports_unformatted_files() {
+    let folder = std::env::temp_dir().join(format!("wipple-fmt-test-{}", std::process::id()));
+    fs::create_dir_all(&folder).unwrap();
+
+    let unformatted = folder.join("unformatted.wpl");
+    fs::write(&unformatted, UNFORMATTED).unwrap();
+    fs::write(folder.join("formatted.wpl"), FORMATTED).unwrap();
+
+    // Files are only reported...
+    let output = fmt(&["--check"], &folder);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("{} is not formatted\n", unformatted.to_string_lossy())
+    );
+    assert_eq!(fs::read_to_string(&unformatted).unwrap(), UNFORMATTED);
+
+    // ...until they're formatted
+    let output = fmt(&[], &folder);
+    assert!(output.status.success());
+    assert_eq!(fs::read_to_string(&unformatted).unwrap(), FORMATTED);
+
+    let output = fmt(&["--check"], &folder);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn files_with_syntax_errors_are_not_formatted() {
+    let folder = std::env::temp_dir().join(format!("wipple-fmt-error-test-{}", std::process::id()));
+    fs::create_dir_all(&folder).unwrap();
+
+    let file = folder.join("error.wpl");
+    fs::write(&file, "x : (1 2\n").unwrap();
+
+    let output = fmt(&[], &folder);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 file(s) could not be formatted"));
+    assert_eq!(fs::read_to_string(&file).unwrap(), "x : (1 2\n");
+
+    fs::remove_dir_all(&folder).unwrap();
+}
@@ -0,0 +1,131 @@
+use std::{
+    fs,
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+/// Start 'wipple repl' with extra options and type the input into it.
+fn repl(input: &str, options: &[&str]) -> Output {
+    let mut process = Command::new(env!("CARGO_BIN_EXE_wipple_cli"))
+        .args(options)
+        .arg("repl")
+        .env("NO_COLOR", "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run wipple");
+
+    process
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+
+    process.wait_with_output().unwrap()
+}
+
+/// The output of the session, without the message shown when it starts.
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout)
+        .strip_prefix("Type :help for a list of commands\n")
+        .expect("the session should start with a message")
+        .to_string()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+#[test]
+fn variables_are_kept_between_inputs() {
+    let output = repl("x : 1\ny : x + 1\nshow y\n", &[]);
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "2\n");
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn input_continues_until_every_bracket_is_closed() {
+    let output = repl("f : x -> [\n    show x\n]\nf 5\n", &[]);
+
+    assert_eq!(stdout(&output), "5\n");
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn env_lists_the_variables_in_the_session() {
+    let output = repl("y : 2\nx : 1\n:env\n", &[]);
+
+    assert_eq!(stdout(&output), "x : 1\ny : 2\n");
+}
+
+#[test]
+fn reset_removes_variables() {
+    let output = repl("x : 1\n:reset\n:env\nshow x\n", &[]);
+
+    assert_eq!(stdout(&output), "");
+    assert!(
+        stderr(&output).starts_with("'x' is not defined\n"),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn load_uses_the_variables_in_a_file() {
+    let folder = std::env::temp_dir().join(format!("wipple-repl-test-{}", std::process::id()));
+    fs::create_dir_all(&folder).unwrap();
+
+    let file = folder.join("greeting.wpl");
+    fs::write(&file, "greeting : \"hello\"\n").unwrap();
+
+    let input = format!(":load {}\nshow greeting\n", file.to_string_lossy());
+    let output = repl(&input, &[]);
+
+    assert_eq!(stdout(&output), "hello\n");
+    assert_eq!(stderr(&output), "");
+
+    let output = repl(":load\n", &[]);
+    assert_eq!(stderr(&output), "Expected a path to a file\n");
+
+    fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn unknown_commands_are_reported() {
+    let output = repl(":frobnicate now\n", &[]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        stderr(&output),
+        "Unknown command ':frobnicate'\nhelp: Type :help for a list of commands\n"
+    );
+
+    // Lines starting with ':' followed by a space are code, not commands
+    let output = repl(": x\n", &[]);
+    assert!(!stderr(&output).contains("Unknown command"));
+}
+
+#[test]
+fn quit_ends_the_session() {
+    let output = repl("show 1\n:quit\nshow 2\n", &[]);
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "1\n");
+}
+
+#[test]
+fn errors_use_the_error_format() {
+    let output = repl("foo\nx )\n:frobnicate\n", &["--error-format", "json"]);
+
+    let errors = stderr(&output);
+    let errors = errors.lines().collect::<Vec<_>>();
+
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    assert!(errors[0].starts_with(r#"{"code":"W0001","message":"'foo' is not defined""#));
+    assert!(errors[1].starts_with(r#"{"code":"W0014","message":"Syntax error: "#));
+    assert!(errors[2].starts_with(r#"{"code":null,"message":"Unknown command ':frobnicate'""#));
+}
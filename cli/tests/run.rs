@@ -0,0 +1,57 @@
+use std::process::{Command, Output};
+
+/// Run 'wipple run' on a piece of code with extra options.
+fn run(code: &str, options: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_wipple_cli"))
+        .arg("run")
+        .args(options)
+        .args(["-e", code])
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("failed to run wipple")
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+const LOOP_FOREVER: &str = "loop [\n    x : 1\n]";
+
+#[test]
+fn max_steps_stops_programs() {
+    let output = run(LOOP_FOREVER, &["--max-steps", "1000"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with("Program took too long to run\n"));
+}
+
+#[test]
+fn timeout_stops_programs() {
+    let output = run(LOOP_FOREVER, &["--timeout", "0.1"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with("Program took too long to run\n"));
+}
+
+#[test]
+fn running_out_of_time_has_an_error_code() {
+    let output = run(
+        LOOP_FOREVER,
+        &["--max-steps", "1000", "--error-format", "json"],
+    );
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stderr(&output).contains(r#""code":"W0011","message":"Program took too long to run""#),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn programs_within_the_budget_run_normally() {
+    let output = run("show (1 + 2)", &["--max-steps", "1000", "--timeout", "10"]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("3\n"));
+}
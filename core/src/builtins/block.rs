@@ -33,15 +33,27 @@ pub(crate) fn setup(env: &mut Environment) {
 
             let mut result = Value::empty();
 
-            for statement in &block.statements {
+            for (index, statement) in block.statements.iter().enumerate() {
                 let mut stack = stack.clone();
                 if let Some(location) = &statement.location {
                     stack.queue_location(location);
                 }
 
-                // Evaluate each statement as a list
+                // Evaluate each statement as a list; the last statement is the
+                // result of the block
                 let list = Value::of(statement.clone());
-                result = list.evaluate(env, &stack)?;
+
+                let statement_result = if index == block.statements.len() - 1 {
+                    list.evaluate_in_tail_position(env, &stack)
+                } else {
+                    list.evaluate(env, &stack)
+                };
+
+                result = match statement_result {
+                    Ok(result) => result,
+                    Err(ReturnState::ReturnFromBlock(value)) => return Ok(value),
+                    Err(state) => return Err(state),
+                };
             }
 
             Ok(result)
@@ -0,0 +1,18 @@
+use crate::*;
+
+#[derive(Clone, Copy)]
+pub struct Boolean(pub bool);
+
+fundamental_primitive!(pub boolean for Boolean);
+
+pub(crate) fn setup(env: &mut Environment) {
+    // true : <boolean>
+    env.set_variable("true", Value::of(Boolean(true)));
+
+    // false : <boolean>
+    env.set_variable("false", Value::of(Boolean(false)));
+
+    env.add_primitive_conformance(|boolean: Boolean| {
+        Text::new(if boolean.0 { "true" } else { "false" })
+    });
+}
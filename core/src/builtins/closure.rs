@@ -9,11 +9,41 @@ pub struct Closure {
 
 fundamental_primitive!(pub closure for Closure);
 
+/// A call to a closure made as the last step of evaluating another closure,
+/// returned instead of being made so the Rust stack doesn't grow.
+#[derive(Clone)]
+pub struct TailCall {
+    pub closure: Closure,
+    pub parameter: Value,
+}
+
+fundamental_primitive!(pub tail_call for TailCall);
+
 pub(crate) fn setup(env: &mut Environment) {
     env.add_primitive_conformance(|closure: Closure| {
         Function::new(move |value, _, stack| {
-            closure.define_parameter.0(value, &closure.captured_env, stack)?;
-            closure.return_value.evaluate(&closure.captured_env, stack)
+            let mut body_stack = stack.clone();
+            body_stack.set_tail_position(true);
+
+            let mut closure = closure.clone();
+            let mut parameter = value.clone();
+
+            // Keep making tail calls until the closure produces a value
+            loop {
+                closure.define_parameter.0(&parameter, &closure.captured_env, stack)?;
+
+                let result = closure
+                    .return_value
+                    .evaluate_in_tail_position(&closure.captured_env, &body_stack)?;
+
+                if !result.has_trait_directly(TraitID::tail_call()) {
+                    return Ok(result);
+                }
+
+                let tail_call = result.get_primitive::<TailCall>(&closure.captured_env, stack)?;
+                closure = tail_call.closure;
+                parameter = tail_call.parameter;
+            }
         })
     });
 
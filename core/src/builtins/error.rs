@@ -0,0 +1,107 @@
+use crate::*;
+
+fundamental_primitive!(pub error for Error);
+
+/// Errors produced when a program reaches one of the limits on its stack or
+/// budget. 'try' doesn't catch these, so programs can't keep running past the
+/// limits meant to stop them.
+const UNCATCHABLE_ERRORS: &[ErrorCode] =
+    &[ErrorCode::RecursionLimitExceeded, ErrorCode::OutOfMemory];
+
+pub(crate) fn setup(env: &mut Environment) {
+    env.add_conformance(TraitID::text(), |value, env, stack| {
+        let error = match value.get_primitive_if_present::<Error>(env, stack)? {
+            Some(error) => error,
+            None => return Ok(None),
+        };
+
+        let text = Text::new(&format!("<error '{}'>", error.message)).allocate(stack)?;
+
+        Ok(Some(Value::of(text)))
+    });
+
+    // Errors are accessed like modules, eg. 'error message'
+    env.add_primitive_conformance(|error: Error| {
+        Function::new(move |value, env, stack| {
+            let name = value.get_primitive_or::<Name>("Expected a name", env, stack)?;
+
+            match name.name.as_str() {
+                "message" => {
+                    let text = Text::new(&error.message).allocate(stack)?;
+
+                    Ok(Value::of(text))
+                }
+                "stack" => {
+                    // The most recent item is first, like when errors are shown
+                    let items = error
+                        .stack
+                        .items
+                        .iter()
+                        .rev()
+                        .map(|item| {
+                            let text = Text::new(&item.to_string()).allocate(stack)?;
+
+                            Ok(Value::of(text))
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+
+                    let list = List::new(&items).allocate(stack)?;
+
+                    Ok(Value::of(list))
+                }
+                _ => Err(ReturnState::Error(Box::new(
+                    Error::new("Errors only have a 'message' and a 'stack'", stack)
+                        .with_code(ErrorCode::UnexpectedValue),
+                ))),
+            }
+        })
+    });
+
+    // try : block -> handler
+    env.set_variable(
+        "try",
+        Value::of(Function::new(|body, _, _| {
+            let body = body.clone();
+
+            Ok(Value::of(Function::new(move |handler, env, stack| {
+                let error = match body.evaluate(env, stack) {
+                    Err(ReturnState::Error(error))
+                        if !error
+                            .code
+                            .is_some_and(|code| UNCATCHABLE_ERRORS.contains(&code)) =>
+                    {
+                        error
+                    }
+                    result => return result,
+                };
+
+                let handler = handler.evaluate(env, stack)?;
+
+                handler.call(&Value::of(Quoted::new(Value::of(*error))), env, stack)
+            })))
+        })),
+    );
+
+    // raise : message-or-error
+    env.set_variable(
+        "raise",
+        Value::of(Function::new(|value, env, stack| {
+            let value = value.evaluate(env, stack)?;
+
+            // Errors that are caught can be raised again as they were
+            if let Some(error) = value.get_primitive_if_present::<Error>(env, stack)? {
+                return Err(ReturnState::Error(Box::new(error)));
+            }
+
+            let message = value.get_primitive_or::<Text>(
+                "Expected a message or an error to raise",
+                env,
+                stack,
+            )?;
+
+            Err(ReturnState::Error(Box::new(
+                Error::new(&message.text, stack).with_code(ErrorCode::Raised),
+            )))
+        })),
+    );
+}
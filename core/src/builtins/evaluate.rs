@@ -14,6 +14,22 @@ fundamental_primitive!(pub evaluate for EvaluateFn);
 
 impl Value {
     pub fn evaluate(&self, env: &EnvironmentRef, stack: &Stack) -> Result {
+        if stack.is_in_tail_position() {
+            let mut stack = stack.clone();
+            stack.set_tail_position(false);
+
+            self.evaluate_in_tail_position(env, &stack)
+        } else {
+            self.evaluate_in_tail_position(env, stack)
+        }
+    }
+
+    /// Evaluate a value whose result is the result of the current evaluation.
+    /// If the stack is in tail position, the result may be a 'TailCall' to be
+    /// made by the closure being called.
+    pub fn evaluate_in_tail_position(&self, env: &EnvironmentRef, stack: &Stack) -> Result {
+        stack.take_step()?;
+
         match self.get_primitive_if_present::<EvaluateFn>(env, stack)? {
             Some(evaluate) => evaluate.0(env, stack),
             None => Ok(self.clone()),
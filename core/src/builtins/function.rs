@@ -14,7 +14,37 @@ fundamental_primitive!(pub function for Function);
 
 impl Value {
     pub fn call(&self, parameter: &Value, env: &EnvironmentRef, stack: &Stack) -> Result {
-        let stack = stack.add(|| format!("Calling '{}'", self.try_format(env, stack)));
+        if stack.is_in_tail_position() {
+            let mut stack = stack.clone();
+            stack.set_tail_position(false);
+
+            self.call_in_tail_position(parameter, env, &stack)
+        } else {
+            self.call_in_tail_position(parameter, env, stack)
+        }
+    }
+
+    /// Call a value whose result is the result of the current evaluation. If
+    /// the stack is in tail position, calls to closures are returned as a
+    /// 'TailCall' instead of being made.
+    pub fn call_in_tail_position(
+        &self,
+        parameter: &Value,
+        env: &EnvironmentRef,
+        stack: &Stack,
+    ) -> Result {
+        stack.take_step()?;
+
+        if stack.is_in_tail_position() {
+            if let Some(closure) = self.get_primitive_if_present::<Closure>(env, stack)? {
+                return Ok(Value::of(TailCall {
+                    closure,
+                    parameter: parameter.clone(),
+                }));
+            }
+        }
+
+        let stack = stack.add_call(|| format!("Calling '{}'", self.try_format(env, stack)))?;
 
         let function = self.get_primitive_or::<Function>(
             "Cannot call this value because it does not have the Function trait",
@@ -4,6 +4,9 @@ use crate::*;
 pub struct List {
     pub items: Vec<Value>,
     pub location: Option<SourceLocation>,
+
+    /// The memory counted against the budget for this list, if any
+    pub allocation: Option<Allocation>,
 }
 
 impl List {
@@ -15,8 +18,21 @@ impl List {
         List {
             items: items.to_vec(),
             location,
+            allocation: None,
         }
     }
+
+    /// The approximate number of bytes used by the list, not including its
+    /// items' own contents.
+    pub fn approximate_size(&self) -> usize {
+        std::mem::size_of::<List>() + self.items.len() * std::mem::size_of::<Value>()
+    }
+
+    /// Count the list's memory against the budget until it's dropped.
+    pub fn allocate(mut self, stack: &Stack) -> Result<Self> {
+        self.allocation = Some(stack.allocate(self.approximate_size())?);
+        Ok(self)
+    }
 }
 
 fundamental_primitive!(pub list for List);
@@ -29,6 +45,10 @@ pub(crate) fn setup(env: &mut Environment) {
                 stack.queue_location(location);
             }
 
+            // Only the last call in the list is in tail position
+            let tail_position = stack.is_in_tail_position();
+            stack.set_tail_position(false);
+
             let operators = list.find_operators(env, &stack)?;
 
             if let Some(parsed) = list.parse_operators(operators, env, &stack)? {
@@ -45,8 +65,15 @@ pub(crate) fn setup(env: &mut Environment) {
                 }
             };
 
-            for item in list.items.iter().skip(1) {
-                result = result.call(item, env, &stack)?;
+            for (index, item) in list.items.iter().enumerate().skip(1) {
+                result = if tail_position && index == list.items.len() - 1 {
+                    let mut stack = stack.clone();
+                    stack.set_tail_position(true);
+
+                    result.call_in_tail_position(item, env, &stack)?
+                } else {
+                    result.call(item, env, &stack)?
+                };
             }
 
             Ok(result)
@@ -63,7 +90,9 @@ pub(crate) fn setup(env: &mut Environment) {
                 expanded_items.push(item);
             }
 
-            Ok(Value::of(List::new(&expanded_items)))
+            let list = List::new(&expanded_items).allocate(stack)?;
+
+            Ok(Value::of(list))
         })
     });
 
@@ -80,9 +109,8 @@ pub(crate) fn setup(env: &mut Environment) {
             items.push(text);
         }
 
-        Ok(Some(Value::of(Text::new(&format!(
-            "({})",
-            items.join(" ")
-        )))))
+        let text = Text::new(&format!("({})", items.join(" "))).allocate(stack)?;
+
+        Ok(Some(Value::of(text)))
     });
 }
@@ -0,0 +1,189 @@
+use crate::*;
+use bigdecimal::ToPrimitive;
+
+// Whether an environment holds the variables of a single loop iteration, in
+// which case assigning to a variable defined outside the loop updates it
+fundamental_env_key!(pub(crate) is_loop_iteration for bool {
+    EnvironmentKey::new(
+        UseFn::take_parent(),
+        false,
+    )
+});
+
+pub(crate) fn setup(env: &mut Environment) {
+    // break : <function>
+    env.set_variable(
+        "break",
+        Value::of(Function::new(|value, env, stack| {
+            let value = value.evaluate(env, stack)?;
+            Err(ReturnState::BreakOutOfLoop(value))
+        })),
+    );
+
+    // continue : <computed>
+    env.set_variable(
+        "continue",
+        Value::of(EvaluateFn::new(|_, _| Err(ReturnState::ContinueLoop)))
+            .add(&Trait::of_primitive(Computed)),
+    );
+
+    // loop : <function>
+    env.set_variable(
+        "loop",
+        Value::of(Function::new(|body, env, stack| {
+            let mut iteration = 1;
+
+            loop {
+                if let Some(value) = evaluate_iteration("loop", iteration, body, env, stack)? {
+                    return Ok(value);
+                }
+
+                iteration += 1;
+            }
+        })),
+    );
+
+    // while : <function>
+    env.set_variable(
+        "while",
+        Value::of(Function::new(|condition, _, _| {
+            let condition = condition.clone();
+
+            Ok(Value::of(Function::new(move |body, env, stack| {
+                // The condition is evaluated again before each iteration
+                for iteration in 1.. {
+                    let condition = condition
+                        .evaluate(env, stack)?
+                        .get_primitive_or::<Boolean>("Expected a boolean", env, stack)?;
+
+                    if !condition.0 {
+                        break;
+                    }
+
+                    if let Some(value) = evaluate_iteration("while", iteration, body, env, stack)? {
+                        return Ok(value);
+                    }
+                }
+
+                Ok(Value::empty())
+            })))
+        })),
+    );
+
+    // repeat : <function>
+    env.set_variable(
+        "repeat",
+        Value::of(Function::new(|count, env, stack| {
+            let count = count.evaluate(env, stack)?.get_primitive_or::<Number>(
+                "Expected a number",
+                env,
+                stack,
+            )?;
+
+            let count = count
+                .number
+                .is_integer()
+                .then(|| count.number.to_usize())
+                .flatten()
+                .ok_or_else(|| {
+                    ReturnState::Error(Box::new(
+                        Error::new("Expected a whole number of times to repeat", stack)
+                            .with_code(ErrorCode::UnexpectedValue),
+                    ))
+                })?;
+
+            Ok(Value::of(Function::new(move |body, env, stack| {
+                for iteration in 1..=count {
+                    if let Some(value) = evaluate_iteration("repeat", iteration, body, env, stack)?
+                    {
+                        return Ok(value);
+                    }
+                }
+
+                Ok(Value::empty())
+            })))
+        })),
+    );
+
+    // for-each : <function>
+    env.set_variable(
+        "for-each",
+        Value::of(Function::new(|parameter, env, stack| {
+            let assign = parameter.get_primitive_or::<AssignFn>(
+                "Loop variable must have the Assign trait",
+                env,
+                stack,
+            )?;
+
+            Ok(Value::of(Function::new(move |sequence, env, stack| {
+                // Any value that can be represented as a list can be iterated
+                let sequence = sequence.evaluate(env, stack)?.get_primitive_or::<List>(
+                    "Expected a list",
+                    env,
+                    stack,
+                )?;
+
+                let assign = assign.clone();
+
+                Ok(Value::of(Function::new(move |body, env, stack| {
+                    for (index, item) in sequence.items.iter().enumerate() {
+                        let iteration_env = Environment::child_of(env).into_ref();
+
+                        // Quote the items so the loop variable refers to the
+                        // items themselves rather than what they evaluate to
+                        assign.0(&Value::of(Quoted::new(item.clone())), &iteration_env, stack)?;
+
+                        // Mark the environment afterwards so the loop variable
+                        // shadows any variable of the same name outside the loop
+                        *iteration_env.borrow_mut().is_loop_iteration() = true;
+
+                        if let Some(value) = evaluate_iteration_in(
+                            "for-each",
+                            index + 1,
+                            body,
+                            &iteration_env,
+                            stack,
+                        )? {
+                            return Ok(value);
+                        }
+                    }
+
+                    Ok(Value::empty())
+                })))
+            })))
+        })),
+    );
+}
+
+/// Evaluate the body of a loop in a new environment, returning the value
+/// passed to 'break' if the loop should stop. Variables defined in the body
+/// only last for one iteration, but assigning to a variable defined outside
+/// the loop updates it.
+fn evaluate_iteration(
+    name: &str,
+    iteration: usize,
+    body: &Value,
+    env: &EnvironmentRef,
+    stack: &Stack,
+) -> Result<Option<Value>> {
+    let iteration_env = Environment::child_of(env).into_ref();
+    *iteration_env.borrow_mut().is_loop_iteration() = true;
+
+    evaluate_iteration_in(name, iteration, body, &iteration_env, stack)
+}
+
+fn evaluate_iteration_in(
+    name: &str,
+    iteration: usize,
+    body: &Value,
+    iteration_env: &EnvironmentRef,
+    stack: &Stack,
+) -> Result<Option<Value>> {
+    let stack = stack.add(|| format!("Evaluating iteration {} of '{}'", iteration, name));
+
+    match body.evaluate(iteration_env, &stack) {
+        Ok(_) | Err(ReturnState::ContinueLoop) => Ok(None),
+        Err(ReturnState::BreakOutOfLoop(value)) => Ok(Some(value)),
+        Err(state) => Err(state),
+    }
+}
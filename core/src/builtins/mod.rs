@@ -1,9 +1,12 @@
 mod block;
+mod boolean;
 mod closure;
 mod empty;
+mod error;
 mod evaluate;
 mod function;
 mod list;
+mod loops;
 mod r#macro;
 mod module;
 mod name;
@@ -16,6 +19,7 @@ mod r#trait;
 mod validation;
 
 pub use block::*;
+pub use boolean::*;
 pub use closure::*;
 pub use evaluate::*;
 pub use function::*;
@@ -35,10 +39,13 @@ use crate::*;
 
 pub(crate) fn setup(env: &mut Environment) {
     block::setup(env);
+    boolean::setup(env);
     closure::setup(env);
     evaluate::setup(env);
     empty::setup(env);
+    error::setup(env);
     list::setup(env);
+    loops::setup(env);
     r#macro::setup(env);
     module::setup(env);
     name::setup(env);
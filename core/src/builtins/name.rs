@@ -45,6 +45,14 @@ pub struct Named {
 
 fundamental_primitive!(pub named for Named);
 
+/// The '---' comments written above an assignment.
+#[derive(Clone)]
+pub struct Documentation {
+    pub text: String,
+}
+
+fundamental_primitive!(pub documentation for Documentation);
+
 pub type Variables = HashMap<String, Value>;
 
 fundamental_env_key!(pub variables for Variables {
@@ -56,8 +64,37 @@ fundamental_env_key!(pub variables for Variables {
     )
 });
 
+// The memory counted against the budget for each variable assigned in an
+// environment, which is given back when the variable is assigned again or the
+// environment is dropped
+fundamental_env_key!(pub(crate) variable_allocations for HashMap<String, Allocation> {
+    EnvironmentKey::new(
+        UseFn::take_parent(),
+        false,
+    )
+});
+
 impl Environment {
     pub fn set_variable(&mut self, name: &str, value: Value) {
+        self.set_variable_with_documentation(name, value, None);
+    }
+
+    pub fn set_variable_with_documentation(
+        &mut self,
+        name: &str,
+        value: Value,
+        documentation: Option<Documentation>,
+    ) {
+        // Trait constructors are named after the first variable they're
+        // assigned to, so their traits can be displayed
+        let value = match &value {
+            Value::Primitive(primitive) => match primitive.try_cast::<TraitConstructor>() {
+                Some(trait_constructor) => Value::of(trait_constructor.clone().named(name)),
+                None => value,
+            },
+            _ => value,
+        };
+
         let name = String::from(name);
 
         // Add a 'Named' trait to the value if it isn't already named
@@ -67,6 +104,11 @@ impl Environment {
             value.add(&Trait::of_primitive(Named { name: name.clone() }))
         };
 
+        let value = match documentation {
+            Some(documentation) => value.add(&Trait::of_primitive(documentation)),
+            None => value,
+        };
+
         self.variables().insert(name, value);
     }
 
@@ -78,10 +120,13 @@ impl Environment {
 
             let named = value.get_primitive_if_present::<Named>(env, stack)?;
 
-            Ok(Some(Value::of(Text::new(&match named {
+            let text = Text::new(&match named {
                 Some(named) => format!("<{} '{}'>", value_name, named.name),
                 None => format!("<{}>", value_name),
-            }))))
+            })
+            .allocate(stack)?;
+
+            Ok(Some(Value::of(text)))
         });
     }
 }
@@ -111,10 +156,57 @@ impl Name {
 
         self.resolve_without_computing_if_present(env)
             .ok_or_else(|| {
-                ReturnState::Error(Error::new("Name does not refer to a variable", &stack))
+                let message = match self.closest_variable(env) {
+                    Some(suggestion) => format!(
+                        "'{}' is not defined; did you mean '{}'?",
+                        self.name, suggestion
+                    ),
+                    None => format!("'{}' is not defined", self.name),
+                };
+
+                ReturnState::Error(Box::new(
+                    Error::new(&message, &stack).with_code(ErrorCode::UndefinedVariable),
+                ))
             })
     }
 
+    /// Find the variable whose name is closest to this name, in case this
+    /// name was misspelled. Only names within a third of this name's length
+    /// of edits are considered.
+    fn closest_variable(&self, env: &EnvironmentRef) -> Option<String> {
+        let max_distance = self.name.chars().count() / 3;
+
+        let mut closest: Option<(usize, String)> = None;
+        let mut env = Some(env.clone());
+
+        while let Some(current_env) = env {
+            for name in current_env.borrow_mut().variables().keys() {
+                let distance = edit_distance(&self.name, name);
+
+                if distance > max_distance {
+                    continue;
+                }
+
+                // Prefer closer names, then alphabetical order so the
+                // suggestion doesn't depend on the order of the variables
+                let is_closer = match &closest {
+                    Some((closest_distance, closest_name)) => {
+                        (distance, name) < (*closest_distance, closest_name)
+                    }
+                    None => true,
+                };
+
+                if is_closer {
+                    closest = Some((distance, name.clone()));
+                }
+            }
+
+            env = current_env.borrow_mut().parent.clone();
+        }
+
+        closest.map(|(_, name)| name)
+    }
+
     pub fn resolve_without_computing_if_present(&self, env: &EnvironmentRef) -> Option<Value> {
         fn get(name: &Name, env: &EnvironmentRef) -> Option<Value> {
             let variable = env.borrow_mut().variables().get(&name.name).cloned();
@@ -130,6 +222,69 @@ impl Name {
     }
 }
 
+/// Find the environment an assignment to 'name' should change. Inside a
+/// loop, a variable that is already defined in the loop's iteration or in the
+/// environment enclosing the loop is updated; otherwise the variable is
+/// defined in the current environment.
+fn assignment_env(name: &str, env: &EnvironmentRef) -> EnvironmentRef {
+    let mut current = env.clone();
+
+    loop {
+        if current.borrow_mut().variables().contains_key(name) {
+            return current;
+        }
+
+        if !*current.borrow_mut().is_loop_iteration() {
+            return env.clone();
+        }
+
+        let parent = current.borrow().parent.clone();
+
+        current = match parent {
+            Some(parent) => parent,
+            None => return env.clone(),
+        };
+    }
+}
+
+/// The number of insertions, deletions, substitutions and swaps of adjacent
+/// characters needed to turn one string into another (the optimal string
+/// alignment distance).
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    // distances[i][j] is the distance between the first i characters of 'a'
+    // and the first j characters of 'b'
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
 pub(crate) fn setup(env: &mut Environment) {
     // Name : trait
     env.set_variable(
@@ -137,19 +292,44 @@ pub(crate) fn setup(env: &mut Environment) {
         Value::of(TraitConstructor {
             id: TraitID::name(),
             validation: Validation::for_primitive::<Name>(),
+            name: None,
         }),
     );
 
-    env.add_primitive_conformance(|name: Name| {
-        AssignFn::new(move |value, env, stack| {
+    env.add_conformance(TraitID::assign(), |name, env, stack| {
+        let documentation = name.get_primitive_if_present::<Documentation>(env, stack)?;
+
+        let name = match name.get_primitive_if_present::<Name>(env, stack)? {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+
+        Ok(Some(Value::of(AssignFn::new(move |value, env, stack| {
             let value = value.evaluate(env, stack)?;
-            env.borrow_mut().set_variable(&name.name, value);
+
+            let allocation =
+                stack.allocate(std::mem::size_of::<(String, Value)>() + name.name.len())?;
+
+            let env = assignment_env(&name.name, env);
+            let mut env = env.borrow_mut();
+
+            env.set_variable_with_documentation(&name.name, value, documentation.clone());
+            env.variable_allocations()
+                .insert(name.name.clone(), allocation);
+
             Ok(())
-        })
+        }))))
     });
 
     env.add_primitive_conformance(|name: Name| {
-        EvaluateFn::new(move |env, stack| name.resolve(env, stack))
+        EvaluateFn::new(move |env, stack| {
+            let mut stack = stack.clone();
+            if let Some(location) = &name.location {
+                stack.queue_location(location);
+            }
+
+            name.resolve(env, &stack)
+        })
     });
 
     env.add_primitive_conformance(|name: Name| {
@@ -174,5 +354,6 @@ pub(crate) fn setup(env: &mut Environment) {
     env.add_primitive_conformance(|name: Name| Text {
         text: name.name,
         location: None,
+        allocation: None,
     });
 }
@@ -23,5 +23,6 @@ pub(crate) fn setup(env: &mut Environment) {
     env.add_primitive_conformance(|number: Number| Text {
         text: number.number.to_string(),
         location: None,
+        allocation: None,
     });
 }
@@ -4,7 +4,6 @@ use std::{
     hash::{Hash, Hasher},
     rc::Rc,
 };
-use uuid::Uuid;
 
 fundamental_env_key!(pub operator_precedences for Vec<PrecedenceGroup> {
     EnvironmentKey::new(
@@ -15,7 +14,7 @@ fundamental_env_key!(pub operator_precedences for Vec<PrecedenceGroup> {
 
 #[derive(Clone)]
 pub struct Operator {
-    pub id: Uuid,
+    pub id: Id,
 
     #[allow(clippy::type_complexity)]
     pub function: Rc<
@@ -37,7 +36,7 @@ impl Operator {
             + 'static,
     ) -> Self {
         Operator {
-            id: Uuid::new_v4(),
+            id: Id::allocate(),
             function: Rc::new(function),
         }
     }
@@ -64,7 +63,7 @@ impl Operator {
         let function = Rc::new(function);
 
         Operator {
-            id: Uuid::new_v4(),
+            id: Id::allocate(),
             function: Rc::new(move |left, _, _| {
                 let left = left.clone();
                 let function = function.clone();
@@ -111,7 +110,7 @@ pub type OperatorList = Vec<(Operator, usize)>;
 
 #[derive(Clone)]
 pub struct PrecedenceGroup {
-    pub id: Uuid,
+    pub id: Id,
     pub operators: HashSet<Operator>,
     pub associativity: Associativity,
 }
@@ -119,7 +118,7 @@ pub struct PrecedenceGroup {
 impl PrecedenceGroup {
     fn new(associativity: Associativity) -> Self {
         PrecedenceGroup {
-            id: Uuid::new_v4(),
+            id: Id::allocate(),
             operators: HashSet::new(),
             associativity,
         }
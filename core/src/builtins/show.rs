@@ -14,10 +14,10 @@ impl ShowFn {
 impl Default for ShowFn {
     fn default() -> Self {
         ShowFn::new(|_, _, stack| {
-            Err(ReturnState::Error(Error::new(
+            Err(ReturnState::Error(Box::new(Error::new(
                 "Cannot use 'show' because this runtime does not handle output",
                 stack,
-            )))
+            ))))
         })
     }
 }
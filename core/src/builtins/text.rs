@@ -4,6 +4,9 @@ use crate::*;
 pub struct Text {
     pub text: String,
     pub location: Option<SourceLocation>,
+
+    /// The memory counted against the budget for this text, if any
+    pub allocation: Option<Allocation>,
 }
 
 impl Text {
@@ -15,8 +18,20 @@ impl Text {
         Text {
             text: String::from(text),
             location,
+            allocation: None,
         }
     }
+
+    /// The approximate number of bytes used by the text.
+    pub fn approximate_size(&self) -> usize {
+        std::mem::size_of::<Text>() + self.text.len()
+    }
+
+    /// Count the text's memory against the budget until it's dropped.
+    pub fn allocate(mut self, stack: &Stack) -> Result<Self> {
+        self.allocation = Some(stack.allocate(self.approximate_size())?);
+        Ok(self)
+    }
 }
 
 fundamental_primitive!(pub text for Text);
@@ -26,6 +41,10 @@ impl Value {
         let mut stack = stack.clone();
         stack.disable_recording();
 
+        // The text is shown by the interpreter rather than the program, so
+        // it's still produced when the program is out of memory
+        stack.budget.max_allocated = None;
+
         match self.get_primitive_if_present::<Text>(env, &stack) {
             Ok(Some(text)) => text.text,
             Ok(None) => String::from("<value>"),
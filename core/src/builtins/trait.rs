@@ -4,10 +4,40 @@ use crate::*;
 pub struct TraitConstructor {
     pub id: TraitID,
     pub validation: Validation,
+
+    /// The name of the variable the trait constructor was first assigned to
+    pub name: Option<String>,
+}
+
+impl TraitConstructor {
+    /// Name the trait constructor and its trait, unless they're already named.
+    pub fn named(mut self, name: &str) -> Self {
+        if self.name.is_none() {
+            self.name = Some(String::from(name));
+        }
+
+        self.id.register_name(name);
+
+        self
+    }
 }
 
 fundamental_primitive!(pub trait_constructor for TraitConstructor);
 
 pub(crate) fn setup(env: &mut Environment) {
-    env.add_text_conformance(TraitID::trait_constructor(), "trait");
+    env.add_conformance(TraitID::text(), |value, env, stack| {
+        let trait_constructor =
+            match value.get_primitive_if_present::<TraitConstructor>(env, stack)? {
+                Some(trait_constructor) => trait_constructor,
+                None => return Ok(None),
+            };
+
+        let text = Text::new(&match trait_constructor.id.display_name() {
+            Some(name) => format!("<trait '{}'>", name),
+            None => String::from("<trait>"),
+        })
+        .allocate(stack)?;
+
+        Ok(Some(Value::of(text)))
+    });
 }
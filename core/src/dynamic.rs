@@ -1,17 +1,13 @@
 use std::{
-    any::{Any, TypeId},
+    any::{type_name, Any, TypeId},
     fmt,
+    hash::{Hash, Hasher},
     rc::Rc,
 };
 
-#[cfg(debug_assertions)]
-use std::any::type_name;
-
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy)]
 pub struct TypeInfo {
     pub id: TypeId,
-
-    #[cfg(debug_assertions)]
     pub name: &'static str,
 }
 
@@ -19,22 +15,35 @@ impl TypeInfo {
     pub fn of<T: 'static>() -> Self {
         TypeInfo {
             id: TypeId::of::<T>(),
-
-            #[cfg(debug_assertions)]
             name: type_name::<T>(),
         }
     }
+
+    /// The name of the type without its path, eg. 'Number'.
+    pub fn short_name(&self) -> &'static str {
+        self.name.rsplit("::").next().unwrap_or(self.name)
+    }
 }
 
-impl fmt::Debug for TypeInfo {
-    #[cfg(debug_assertions)]
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.name)
+// Types are compared often, so only compare their IDs and not their names
+
+impl PartialEq for TypeInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
     }
+}
 
-    #[cfg(not(debug_assertions))]
+impl Eq for TypeInfo {}
+
+impl Hash for TypeInfo {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl fmt::Debug for TypeInfo {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "(TypeInfo)")
+        write!(f, "{}", self.name)
     }
 }
 
@@ -16,6 +16,10 @@ fundamental_env_key!(pub conformances for Conformances {
 pub struct Conformance {
     pub derived_trait_id: TraitID,
 
+    /// The trait the derived trait is derived from, if known, which is used to
+    /// explain why a trait couldn't be found
+    pub source_trait_id: Option<TraitID>,
+
     #[allow(clippy::type_complexity)]
     pub derive_trait_value: Rc<dyn Fn(&Value, &EnvironmentRef, &Stack) -> Result<Option<Value>>>,
 }
@@ -25,9 +29,19 @@ impl Environment {
         &mut self,
         derived_trait_id: TraitID,
         derive_trait_value: impl Fn(&Value, &EnvironmentRef, &Stack) -> Result<Option<Value>> + 'static,
+    ) {
+        self.add_conformance_from(derived_trait_id, None, derive_trait_value)
+    }
+
+    fn add_conformance_from(
+        &mut self,
+        derived_trait_id: TraitID,
+        source_trait_id: Option<TraitID>,
+        derive_trait_value: impl Fn(&Value, &EnvironmentRef, &Stack) -> Result<Option<Value>> + 'static,
     ) {
         self.conformances().push(Conformance {
             derived_trait_id,
+            source_trait_id,
             derive_trait_value: Rc::new(derive_trait_value),
         })
     }
@@ -36,15 +50,19 @@ impl Environment {
         &mut self,
         derive_trait_value: impl Fn(A) -> B + 'static,
     ) {
-        self.add_conformance(TraitID::new_primitive::<B>(), move |value, env, stack| {
-            let a = match value.get_primitive_if_present::<A>(env, stack)? {
-                Some(primitive) => primitive,
-                None => return Ok(None),
-            };
+        self.add_conformance_from(
+            TraitID::new_primitive::<B>(),
+            Some(TraitID::new_primitive::<A>()),
+            move |value, env, stack| {
+                let a = match value.get_primitive_if_present::<A>(env, stack)? {
+                    Some(primitive) => primitive,
+                    None => return Ok(None),
+                };
 
-            let b = derive_trait_value(a);
+                let b = derive_trait_value(a);
 
-            Ok(Some(Value::of(b)))
-        });
+                Ok(Some(Value::of(b)))
+            },
+        );
     }
 }
@@ -1,10 +1,30 @@
-use std::{fmt, path::PathBuf};
+use crate::{ErrorCode, Result, Value};
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+use std::{
+    cell::Cell,
+    fmt,
+    ops::Range,
+    path::PathBuf,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SourceLocation {
     pub file: Option<PathBuf>,
     pub line: usize,
     pub column: usize,
+
+    /// The byte offsets of the start and end (exclusive) of the code in the
+    /// file
+    pub start: usize,
+    pub end: usize,
+}
+
+impl SourceLocation {
+    pub fn span(&self) -> Range<usize> {
+        self.start..self.end
+    }
 }
 
 impl fmt::Display for SourceLocation {
@@ -22,14 +42,159 @@ impl fmt::Display for SourceLocation {
     }
 }
 
+/// The approximate amount of native stack space each nested call takes up.
+/// Measured by recursing without tail calls until the stack overflows, then
+/// rounded up to leave room for the blocks and operators evaluated between
+/// calls.
+#[cfg(debug_assertions)]
+const STACK_SIZE_PER_CALL: usize = 48 * 1024;
+
+#[cfg(not(debug_assertions))]
+const STACK_SIZE_PER_CALL: usize = 10 * 1024;
+
+/// The maximum number of nested calls that fit in a native stack of this many
+/// bytes. Hosts that know the size of their stack use this to set
+/// 'Stack::max_depth'.
+pub const fn max_depth_for_stack_size(stack_size: usize) -> usize {
+    stack_size / STACK_SIZE_PER_CALL
+}
+
+/// The size of the native stack assumed on threads not started by
+/// 'run_on_large_stack': the size of threads spawned by Rust's standard
+/// library. Main threads usually have more (8 MiB on Linux and macOS).
+const DEFAULT_STACK_SIZE: usize = 2 * 1024 * 1024;
+
+/// The default maximum number of nested calls, so deeply recursive programs
+/// fail with an error before running out of native stack space.
+pub const DEFAULT_MAX_STACK_DEPTH: usize = max_depth_for_stack_size(DEFAULT_STACK_SIZE);
+
+/// The size of the native stack of threads started by 'run_on_large_stack'.
+pub const LARGE_STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// The default maximum number of nested calls on threads started by
+/// 'run_on_large_stack'. The stack has room for more calls than this, but each
+/// call keeps its own copy of the stack's items, so the memory and time used
+/// grow with the square of the depth.
+pub const LARGE_STACK_MAX_DEPTH: usize = 2000;
+
+thread_local! {
+    static IS_LARGE_STACK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run 'f' on a new thread with a large native stack and wait for it to
+/// finish. Stacks created on the thread allow 'LARGE_STACK_MAX_DEPTH' nested
+/// calls by default, so programs can recurse more deeply than on the main
+/// thread.
+pub fn run_on_large_stack<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+    std::thread::Builder::new()
+        .stack_size(LARGE_STACK_SIZE)
+        .spawn(|| {
+            IS_LARGE_STACK.with(|is_large_stack| is_large_stack.set(true));
+            f()
+        })
+        .expect("failed to start thread")
+        .join()
+        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+/// Memory counted against a budget by 'Stack::allocate'. The memory is given
+/// back to the budget when the allocation and all of its copies are dropped,
+/// so keep the allocation alongside the value using the memory.
+#[derive(Debug, Clone)]
+pub struct Allocation {
+    // Only kept so the memory is given back when it's dropped
+    _inner: Rc<AllocationInner>,
+}
+
+#[derive(Debug)]
+struct AllocationInner {
+    bytes: usize,
+    allocated: Rc<Cell<usize>>,
+}
+
+impl Drop for AllocationInner {
+    fn drop(&mut self) {
+        self.allocated.set(self.allocated.get() - self.bytes);
+    }
+}
+
+/// The number of items shown when the maximum stack depth is exceeded.
+const FRAMES_SHOWN_WHEN_TOO_DEEP: usize = 10;
+
+/// Limits how long a program can run and how much memory it can hold, so
+/// programs that never finish or grow forever can be stopped. Copies of a
+/// budget share the number of steps taken and the memory allocated.
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    /// The maximum number of evaluations and calls
+    pub max_steps: Option<usize>,
+
+    /// The time by which the program must finish. Checking the time isn't
+    /// supported in the browser, so the playground only limits the number of
+    /// steps.
+    pub deadline: Option<Instant>,
+
+    /// The maximum number of bytes held at once by lists, text and
+    /// variables. Memory is given back when the values using it are dropped,
+    /// so programs that keep allocating and dropping values can run forever.
+    pub max_allocated: Option<usize>,
+
+    steps: Rc<Cell<usize>>,
+    allocated: Rc<Cell<usize>>,
+}
+
+impl Budget {
+    pub fn new() -> Self {
+        Budget::default()
+    }
+
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.deadline = Some(Instant::now() + timeout);
+        self
+    }
+
+    pub fn with_max_allocated(mut self, max_allocated: usize) -> Self {
+        self.max_allocated = Some(max_allocated);
+        self
+    }
+
+    /// The number of evaluations and calls made so far.
+    pub fn steps(&self) -> usize {
+        self.steps.get()
+    }
+
+    /// The approximate number of bytes currently held by the program.
+    pub fn allocated(&self) -> usize {
+        self.allocated.get()
+    }
+}
+
 // TODO: Use dynamic key-based storage like Environment
 #[derive(Clone)]
 pub struct Stack {
     pub items: Vec<StackItem>,
     pub project_root: Option<PathBuf>,
     pub current_file: Option<PathBuf>,
+
+    /// The paths of the files and folders being imported, outermost first
+    pub imports: Vec<PathBuf>,
+
+    /// Whether importing a project can install its dependencies, which may
+    /// clone Git repositories and write the project's lockfile. Otherwise only
+    /// dependencies that are already installed can be imported.
+    pub can_install_dependencies: bool,
+
+    pub max_depth: usize,
+    pub budget: Budget,
+    depth: usize,
     queued_location: Option<SourceLocation>,
     recording_enabled: bool,
+    tail_position: bool,
 }
 
 impl Stack {
@@ -38,8 +203,18 @@ impl Stack {
             items: vec![],
             project_root: None,
             current_file: None,
+            imports: vec![],
+            can_install_dependencies: true,
+            max_depth: if IS_LARGE_STACK.with(Cell::get) {
+                LARGE_STACK_MAX_DEPTH
+            } else {
+                DEFAULT_MAX_STACK_DEPTH
+            },
+            budget: Budget::new(),
+            depth: 0,
             queued_location: None,
             recording_enabled: true,
+            tail_position: false,
         }
     }
 
@@ -51,6 +226,16 @@ impl Stack {
         self.recording_enabled = false;
     }
 
+    /// Whether the value being evaluated is the result of a closure, meaning
+    /// calls to other closures can be made after the closure returns
+    pub fn is_in_tail_position(&self) -> bool {
+        self.tail_position
+    }
+
+    pub fn set_tail_position(&mut self, tail_position: bool) {
+        self.tail_position = tail_position;
+    }
+
     pub fn add_item(&self, item: impl FnOnce() -> StackItem) -> Self {
         if !self.recording_enabled {
             return self.clone();
@@ -72,6 +257,91 @@ impl Stack {
         stack
     }
 
+    /// The number of calls that haven't returned yet. Calls made in tail
+    /// position don't count, because they return before the next call is made.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Add an item for a call, failing if there are more than 'max_depth'
+    /// calls that haven't returned yet. Only the most recent items are shown
+    /// in the error.
+    pub fn add_call(&self, label: impl FnOnce() -> String) -> Result<Self> {
+        let mut stack = self.add(label);
+        stack.depth += 1;
+
+        if stack.depth <= stack.max_depth {
+            return Ok(stack);
+        }
+
+        let hidden = stack.items.len().saturating_sub(FRAMES_SHOWN_WHEN_TOO_DEEP);
+
+        if hidden > 0 {
+            stack.items = std::iter::once(StackItem {
+                label: format!("...and {} more", hidden),
+                location: None,
+            })
+            .chain(stack.items.drain(hidden..))
+            .collect();
+        }
+
+        Err(ReturnState::Error(Box::new(
+            Error::new("Maximum recursion depth exceeded", &stack)
+                .with_code(ErrorCode::RecursionLimitExceeded)
+                .with_help(
+                    "Make the recursive call the result of the function so it doesn't need more space",
+                ),
+        )))
+    }
+
+    /// Count an evaluation or call against the budget, failing if the program
+    /// has taken too many steps or run past its deadline.
+    pub fn take_step(&self) -> Result<()> {
+        let steps = self.budget.steps.get() + 1;
+        self.budget.steps.set(steps);
+
+        let exceeded = self
+            .budget
+            .max_steps
+            .is_some_and(|max_steps| steps > max_steps)
+            || self
+                .budget
+                .deadline
+                .is_some_and(|deadline| Instant::now() > deadline);
+
+        if exceeded {
+            Err(ReturnState::OutOfTime(Box::new(
+                Error::new("Program took too long to run", self).with_code(ErrorCode::OutOfTime),
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Count memory allocated by the program against the budget until the
+    /// returned allocation is dropped, failing if the program holds too much.
+    pub fn allocate(&self, bytes: usize) -> Result<Allocation> {
+        let allocated = self.budget.allocated.get() + bytes;
+
+        if let Some(max_allocated) = self.budget.max_allocated {
+            if allocated > max_allocated {
+                return Err(ReturnState::Error(Box::new(
+                    Error::new("Program allocated too much memory", self)
+                        .with_code(ErrorCode::OutOfMemory),
+                )));
+            }
+        }
+
+        self.budget.allocated.set(allocated);
+
+        Ok(Allocation {
+            _inner: Rc::new(AllocationInner {
+                bytes,
+                allocated: self.budget.allocated.clone(),
+            }),
+        })
+    }
+
     pub fn add(&self, label: impl FnOnce() -> String) -> Self {
         self.add_item(|| StackItem {
             label: label(),
@@ -108,12 +378,19 @@ impl fmt::Debug for Stack {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct StackItem {
     pub label: String,
     pub location: Option<SourceLocation>,
 }
 
+impl StackItem {
+    /// The byte offsets of the code this item refers to, if known.
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.location.as_ref().map(SourceLocation::span)
+    }
+}
+
 impl fmt::Display for StackItem {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match &self.location {
@@ -125,9 +402,25 @@ impl fmt::Display for StackItem {
 
 #[derive(Debug, Clone)]
 pub enum ReturnState {
-    ReturnFromBlock,
-    BreakOutOfLoop,
-    Error(Error),
+    /// Produced by 'return' and caught by the closest block, which evaluates
+    /// to the value
+    ReturnFromBlock(Value),
+
+    /// Produced by 'break' and caught by the closest loop, which evaluates to
+    /// the value
+    BreakOutOfLoop(Value),
+
+    /// Produced by 'continue' and caught by the closest loop, which moves on
+    /// to the next iteration
+    ContinueLoop,
+
+    /// Produced when the stack's budget runs out, so hosts can tell programs
+    /// that took too long apart from programs with errors
+    OutOfTime(Box<Error>),
+
+    /// Errors are boxed so results stay small, since they're returned from
+    /// nearly every function
+    Error(Box<Error>),
 }
 
 impl ReturnState {
@@ -137,42 +430,92 @@ impl ReturnState {
         use ReturnState::*;
 
         match self {
-            ReturnFromBlock => crate::Error::new("'return' outside block", stack),
-            BreakOutOfLoop => crate::Error::new("'break' outside loop", stack),
-            Error(error) => error,
+            ReturnFromBlock(_) => crate::Error::new("'return' outside block", stack)
+                .with_code(ErrorCode::ReturnOutsideBlock),
+            BreakOutOfLoop(_) => crate::Error::new("'break' outside loop", stack)
+                .with_code(ErrorCode::BreakOutsideLoop),
+            ContinueLoop => crate::Error::new("'continue' outside loop", stack)
+                .with_code(ErrorCode::ContinueOutsideLoop),
+            OutOfTime(error) | Error(error) => *error,
         }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Error {
+    pub code: Option<ErrorCode>,
     pub message: String,
     pub stack: Stack,
+
+    /// Extra information about why the error happened
+    pub notes: Vec<String>,
+
+    /// A suggestion for how to fix the error
+    pub help: Option<String>,
 }
 
 impl Error {
     pub fn new(message: &str, stack: &Stack) -> Self {
         Error {
+            code: None,
             message: String::from(message),
             stack: stack.clone(),
+            notes: Vec::new(),
+            help: None,
         }
     }
+
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Self {
+        self.notes.push(String::from(note));
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.help = Some(String::from(help));
+        self
+    }
+
+    /// Serialize the error as JSON for tools to read. The stack is listed
+    /// with the most recent item first, like when the error is displayed.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{}\n{}",
-            self.message,
-            self.stack
-                .items
-                .iter()
-                .rev()
-                .map(|item| format!("    {}", item))
-                .collect::<Vec<_>>()
-                .join("\n")
-        )
+        write!(f, "{}", self.message)?;
+
+        for item in self.stack.items.iter().rev() {
+            write!(f, "\n    {}", item)?;
+        }
+
+        for note in &self.notes {
+            write!(f, "\nnote: {}", note)?;
+        }
+
+        if let Some(help) = &self.help {
+            write!(f, "\nhelp: {}", help)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut error = serializer.serialize_struct("Error", 5)?;
+        error.serialize_field("code", &self.code)?;
+        error.serialize_field("message", &self.message)?;
+        error.serialize_field("stack", &self.stack.items.iter().rev().collect::<Vec<_>>())?;
+        error.serialize_field("notes", &self.notes)?;
+        error.serialize_field("help", &self.help)?;
+        error.end()
     }
 }
 
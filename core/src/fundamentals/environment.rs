@@ -6,7 +6,6 @@ use std::{
     hash::{Hash, Hasher},
     rc::Rc,
 };
-use uuid::Uuid;
 
 pub type EnvironmentValues = HashMap<EnvironmentKey, Dynamic>;
 pub type EnvironmentRef = Rc<RefCell<Environment>>;
@@ -103,7 +102,7 @@ impl Environment {
 
 #[derive(Clone)]
 pub struct EnvironmentKey {
-    pub id: Uuid,
+    pub id: Id,
     pub r#use: UseFn,
     /// What to do when the key is not in the parent environment
     pub insert: bool,
@@ -112,7 +111,7 @@ pub struct EnvironmentKey {
 impl EnvironmentKey {
     pub fn new(r#use: UseFn, insert: bool) -> Self {
         EnvironmentKey {
-            id: Uuid::new_v4(),
+            id: Id::allocate_permanent(),
             r#use,
             insert,
         }
@@ -0,0 +1,91 @@
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+/// Identifies the kind of an error, so tools can tell errors apart without
+/// matching their messages. Codes are stable: new kinds of errors get new
+/// codes, and codes are never reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// A name was used that doesn't refer to a variable
+    UndefinedVariable,
+
+    /// A value doesn't have a trait it needs, eg. a number was expected but a
+    /// piece of text was provided
+    MissingTrait,
+
+    /// A value has the right traits but can't be used, eg. a fractional
+    /// number of times to repeat
+    UnexpectedValue,
+
+    /// A value satisfies more than one conformance for the same trait
+    AmbiguousConformance,
+
+    /// A trait's validation rejected a value
+    InvalidTraitValue,
+
+    /// Two values were compared that can't be compared
+    InvalidComparison,
+
+    /// 'return' was used outside a block
+    ReturnOutsideBlock,
+
+    /// 'break' was used outside a loop
+    BreakOutsideLoop,
+
+    /// 'continue' was used outside a loop
+    ContinueOutsideLoop,
+
+    /// The stack grew deeper than its maximum depth
+    RecursionLimitExceeded,
+
+    /// The program took more steps or time than its budget allowed
+    OutOfTime,
+
+    /// The program allocated more memory than its budget allowed
+    OutOfMemory,
+
+    /// The program raised an error using 'raise'
+    Raised,
+
+    /// The code couldn't be parsed
+    SyntaxError,
+
+    /// A file or dependency couldn't be imported
+    ImportFailed,
+}
+
+impl ErrorCode {
+    pub fn code(&self) -> &'static str {
+        use ErrorCode::*;
+
+        match self {
+            UndefinedVariable => "W0001",
+            MissingTrait => "W0002",
+            UnexpectedValue => "W0003",
+            AmbiguousConformance => "W0004",
+            InvalidTraitValue => "W0005",
+            InvalidComparison => "W0006",
+            ReturnOutsideBlock => "W0007",
+            BreakOutsideLoop => "W0008",
+            ContinueOutsideLoop => "W0009",
+            RecursionLimitExceeded => "W0010",
+            OutOfTime => "W0011",
+            OutOfMemory => "W0012",
+            Raised => "W0013",
+            SyntaxError => "W0014",
+            ImportFailed => "W0015",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
@@ -1,12 +1,14 @@
 mod conformances;
 mod diagnostics;
 mod environment;
+mod error_codes;
 mod traits;
 mod values;
 
 pub use conformances::*;
 pub use diagnostics::*;
 pub use environment::*;
+pub use error_codes::*;
 pub use traits::*;
 pub use values::*;
 
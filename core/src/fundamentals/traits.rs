@@ -1,14 +1,16 @@
 use crate::*;
 use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
     hash::{Hash, Hasher},
     rc::Rc,
 };
-use uuid::Uuid;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TraitID {
     Primitive(TypeInfo),
-    Runtime(Uuid),
+    Runtime(Id),
 }
 
 impl TraitID {
@@ -17,7 +19,50 @@ impl TraitID {
     }
 
     pub fn new_runtime() -> Self {
-        TraitID::Runtime(Uuid::new_v4())
+        TraitID::Runtime(Id::allocate())
+    }
+}
+
+thread_local! {
+    static TRAIT_NAMES: RefCell<HashMap<TraitID, String>> = RefCell::new(HashMap::new());
+}
+
+impl TraitID {
+    /// Give the trait a name to use when it's displayed. Traits keep the first
+    /// name they're given.
+    pub fn register_name(&self, name: &str) {
+        TRAIT_NAMES.with(|names| {
+            names
+                .borrow_mut()
+                .entry(*self)
+                .or_insert_with(|| String::from(name));
+        });
+    }
+
+    /// Forget the names of every trait, so traits allocated after 'Id::reset'
+    /// don't take the names of earlier traits.
+    pub(crate) fn forget_names() {
+        TRAIT_NAMES.with(|names| names.borrow_mut().clear());
+    }
+
+    /// The trait's registered name, or the name of its type for primitive
+    /// traits.
+    pub fn display_name(&self) -> Option<String> {
+        let registered_name = TRAIT_NAMES.with(|names| names.borrow().get(self).cloned());
+
+        registered_name.or_else(|| match self {
+            TraitID::Primitive(type_info) => Some(String::from(type_info.short_name())),
+            TraitID::Runtime(_) => None,
+        })
+    }
+}
+
+impl fmt::Display for TraitID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.display_name() {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "<unnamed trait>"),
+        }
     }
 }
 
@@ -75,7 +120,12 @@ fundamental_env_key!(is_deriving_from_conformance for bool {
 
 impl Value {
     pub fn get_trait(&self, id: TraitID, env: &EnvironmentRef, stack: &Stack) -> Result {
-        self.get_trait_or(id, "Cannot find trait", env, stack)
+        self.get_trait_if_present(id, env, stack)?.ok_or_else(|| {
+            let message = format!("Cannot find trait '{}'", id);
+            ReturnState::Error(Box::new(
+                self.missing_trait_error(id, &message, false, env, stack),
+            ))
+        })
     }
 
     pub fn has_trait(&self, id: TraitID, env: &EnvironmentRef, stack: &Stack) -> Result<bool> {
@@ -94,8 +144,82 @@ impl Value {
         env: &EnvironmentRef,
         stack: &Stack,
     ) -> Result {
-        self.get_trait_if_present(id, env, stack)?
-            .ok_or_else(|| ReturnState::Error(Error::new(message, stack)))
+        self.get_trait_if_present(id, env, stack)?.ok_or_else(|| {
+            ReturnState::Error(Box::new(
+                self.missing_trait_error(id, message, true, env, stack),
+            ))
+        })
+    }
+
+    /// Explain why the value doesn't have a trait: which traits it does have,
+    /// and which conformances could have derived the trait but didn't apply.
+    fn missing_trait_error(
+        &self,
+        id: TraitID,
+        message: &str,
+        mention_trait: bool,
+        env: &EnvironmentRef,
+        stack: &Stack,
+    ) -> Error {
+        let mut error = Error::new(message, stack).with_code(ErrorCode::MissingTrait);
+
+        if mention_trait {
+            error = error.with_note(&format!("Expected a value with the '{}' trait", id));
+        }
+
+        let mut traits = self
+            .traits()
+            .iter()
+            .map(|r#trait| format!("'{}'", r#trait.id))
+            .collect::<Vec<_>>();
+
+        traits.sort();
+
+        error = if traits.is_empty() {
+            error.with_note("This value has no traits")
+        } else {
+            error.with_note(&format!("This value has the traits {}", traits.join(", ")))
+        };
+
+        let mut sources = Vec::new();
+        let mut conformance_count = 0;
+        let mut env = Some(env.clone());
+
+        while let Some(current_env) = env {
+            for conformance in current_env.borrow_mut().conformances().iter() {
+                if conformance.derived_trait_id != id {
+                    continue;
+                }
+
+                conformance_count += 1;
+
+                if let Some(source) = conformance.source_trait_id {
+                    let source = format!("'{}'", source);
+
+                    if !sources.contains(&source) {
+                        sources.push(source);
+                    }
+                }
+            }
+
+            env = current_env.borrow_mut().parent.clone();
+        }
+
+        if conformance_count > 0 {
+            let mut note = format!(
+                "Tried {} conformance(s) deriving '{}', but none applied to this value",
+                conformance_count, id
+            );
+
+            if !sources.is_empty() {
+                sources.sort();
+                note.push_str(&format!(" (derived from {})", sources.join(", ")));
+            }
+
+            error = error.with_note(&note);
+        }
+
+        error
     }
 
     pub fn get_trait_if_present(
@@ -105,10 +229,20 @@ impl Value {
         stack: &Stack,
     ) -> Result<Option<Value>> {
         // Always use traits directly defined on the value if they exist instead
-        // of deriving them
-        if let Some(r#trait) = self.traits().iter().find(|t| t.id == id) {
-            let trait_value = (r#trait.value)(env, stack)?;
-            return Ok(Some(trait_value));
+        // of deriving them (checking primitives without building their set of
+        // traits, since this is done very often)
+        match self {
+            Value::Primitive(primitive) => {
+                if TraitID::Primitive(primitive.type_info) == id {
+                    return Ok(Some(self.clone()));
+                }
+            }
+            Value::Composite(traits) => {
+                if let Some(r#trait) = traits.iter().find(|t| t.id == id) {
+                    let trait_value = (r#trait.value)(env, stack)?;
+                    return Ok(Some(trait_value));
+                }
+            }
         }
 
         // Don't derive traits from conformances if we're already deriving
@@ -125,12 +259,15 @@ impl Value {
         let mut env = env.clone();
 
         loop {
+            // Only clone the conformances that can derive the trait, since
+            // this is done every time a value is evaluated or called
             let conformances = env
                 .borrow_mut()
                 .conformances()
-                .clone()
-                .into_iter()
-                .filter(|c| c.derived_trait_id == id);
+                .iter()
+                .filter(|c| c.derived_trait_id == id)
+                .cloned()
+                .collect::<Vec<_>>();
 
             let mut derived_trait = None;
 
@@ -141,9 +278,12 @@ impl Value {
 
                 if let Some(derived_value) = (conformance.derive_trait_value)(self, &env, stack)? {
                     if derived_trait.is_some() {
-                        return Err(ReturnState::Error(Error::new(
-                            "Value satisfies multiple conformances, so the conformance to use is ambiguous",
-                            stack
+                        return Err(ReturnState::Error(Box::new(
+                            Error::new(
+                                "Value satisfies multiple conformances, so the conformance to use is ambiguous",
+                                stack,
+                            )
+                            .with_code(ErrorCode::AmbiguousConformance),
                         )));
                     }
 
@@ -170,7 +310,14 @@ impl Value {
 
 impl Value {
     pub fn get_primitive<T: Primitive>(&self, env: &EnvironmentRef, stack: &Stack) -> Result<T> {
-        self.get_primitive_or("Cannot find trait", env, stack)
+        let id = TraitID::new_primitive::<T>();
+
+        self.get_primitive_if_present(env, stack)?.ok_or_else(|| {
+            let message = format!("Cannot find trait '{}'", id);
+            ReturnState::Error(Box::new(
+                self.missing_trait_error(id, &message, false, env, stack),
+            ))
+        })
     }
 
     pub fn get_primitive_or<T: Primitive>(
@@ -179,8 +326,12 @@ impl Value {
         env: &EnvironmentRef,
         stack: &Stack,
     ) -> Result<T> {
-        self.get_primitive_if_present(env, stack)?
-            .ok_or_else(|| ReturnState::Error(Error::new(message, stack)))
+        self.get_primitive_if_present(env, stack)?.ok_or_else(|| {
+            let id = TraitID::new_primitive::<T>();
+            ReturnState::Error(Box::new(
+                self.missing_trait_error(id, message, true, env, stack),
+            ))
+        })
     }
 
     pub fn get_primitive_if_present<T: Primitive>(
@@ -1,5 +1,5 @@
 use crate::*;
-use std::collections::HashSet;
+use std::{collections::HashSet, fmt};
 
 pub trait Primitive: Clone + 'static {}
 
@@ -61,3 +61,16 @@ impl Value {
         }
     }
 }
+
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Primitive(primitive) => write!(f, "(Value {:?})", primitive),
+            Value::Composite(traits) => write!(
+                f,
+                "(Value {:?})",
+                traits.iter().map(|t| t.id).collect::<Vec<_>>()
+            ),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::{cell::Cell, fmt};
+
+/// Identifies operators, precedence groups, runtime traits and environment
+/// keys. IDs are allocated in order from a counter that's reset by 'setup',
+/// so the same program gets the same IDs every time it's run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id(u64);
+
+thread_local! {
+    static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+    static NEXT_PERMANENT_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+impl Id {
+    pub fn allocate() -> Self {
+        NEXT_ID.with(|next_id| {
+            let id = next_id.get();
+            next_id.set(id + 1);
+            Id(id)
+        })
+    }
+
+    /// Allocate an ID from a counter that's never reset, for things that live
+    /// as long as the thread, like environment keys. These IDs are only
+    /// unique among each other.
+    pub fn allocate_permanent() -> Self {
+        NEXT_PERMANENT_ID.with(|next_id| {
+            let id = next_id.get();
+            next_id.set(id + 1);
+            Id(id)
+        })
+    }
+
+    /// Allocate IDs from the start again. Only call this once nothing
+    /// allocated before is in use, eg. when the global environment is
+    /// replaced.
+    pub(crate) fn reset() {
+        NEXT_ID.with(|next_id| next_id.set(0));
+    }
+}
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
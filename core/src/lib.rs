@@ -67,9 +67,11 @@ macro_rules! env_key {
 mod builtins;
 mod dynamic;
 mod fundamentals;
+mod id;
 mod prelude;
 
 pub use builtins::*;
 pub use dynamic::*;
 pub use fundamentals::*;
+pub use id::*;
 pub use prelude::{setup, *};
@@ -1,6 +1,11 @@
 use crate::*;
 
 pub fn setup() {
+    // Nothing from previous runs is used once the global environment is
+    // replaced, so programs get the same IDs every time they're run
+    Id::reset();
+    TraitID::forget_names();
+
     let env = Environment::global();
     *env.borrow_mut() = Environment::blank();
 
@@ -21,16 +26,20 @@ fn temporary_prelude(env: &EnvironmentRef) {
     ) -> Value {
         let captured_env = Environment::child_of(env).into_ref();
 
-        let r#trait = Trait::new(
-            trait_constructor.id,
-            move |_, stack| match trait_constructor.validation.0(&value, &captured_env, stack)? {
+        let id = trait_constructor.id;
+
+        let r#trait = Trait::new(id, move |_, stack| {
+            match trait_constructor.validation.0(&value, &captured_env, stack)? {
                 Validated::Valid(value) => Ok(value),
-                Validated::Invalid => Err(ReturnState::Error(Error::new(
-                    "Cannot use this value to represent this trait",
-                    stack,
+                Validated::Invalid => Err(ReturnState::Error(Box::new(
+                    Error::new(
+                        &format!("Cannot use this value to represent the trait '{}'", id),
+                        stack,
+                    )
+                    .with_code(ErrorCode::InvalidTraitValue),
                 ))),
-            },
-        );
+            }
+        });
 
         base.add(&r#trait)
     }
@@ -53,6 +62,25 @@ fn temporary_prelude(env: &EnvironmentRef) {
         })),
     );
 
+    // 'trait' function
+
+    env.borrow_mut().set_variable(
+        "trait",
+        Value::of(Function::new(|value, env, stack| {
+            let validation = value.evaluate(env, stack)?.get_primitive_or::<Validation>(
+                "Expected a validation for the trait",
+                env,
+                stack,
+            )?;
+
+            Ok(Value::of(TraitConstructor {
+                id: TraitID::new_runtime(),
+                validation,
+                name: None,
+            }))
+        })),
+    );
+
     // 'do' function
 
     env.borrow_mut().set_variable(
@@ -63,6 +91,39 @@ fn temporary_prelude(env: &EnvironmentRef) {
         })),
     );
 
+    // 'if' function
+
+    env.borrow_mut().set_variable(
+        "if",
+        Value::of(Function::new(|condition, env, stack| {
+            let condition = condition
+                .evaluate(env, stack)?
+                .get_primitive_or::<Boolean>("Expected a boolean", env, stack)?;
+
+            Ok(Value::of(Function::new(move |then, _, _| {
+                let then = then.clone();
+
+                Ok(Value::of(Function::new(move |otherwise, env, stack| {
+                    // Only the chosen branch is evaluated
+                    let branch = if condition.0 { &then } else { otherwise };
+
+                    let inner_env = Environment::child_of(env).into_ref();
+                    branch.evaluate_in_tail_position(&inner_env, stack)
+                })))
+            })))
+        })),
+    );
+
+    // 'return' function
+
+    env.borrow_mut().set_variable(
+        "return",
+        Value::of(Function::new(|value, env, stack| {
+            let value = value.evaluate(env, stack)?;
+            Err(ReturnState::ReturnFromBlock(value))
+        })),
+    );
+
     // 'use' function
 
     env.borrow_mut().set_variable(
@@ -122,9 +183,9 @@ fn temporary_prelude(env: &EnvironmentRef) {
                     right.items[1].evaluate(env, stack)?,
                 ),
                 _ => {
-                    return Err(ReturnState::Error(Error::new(
-                        "Expected a trait and a value for the trati",
-                        stack,
+                    return Err(ReturnState::Error(Box::new(
+                        Error::new("Expected a trait and a value for the trati", stack)
+                            .with_code(ErrorCode::UnexpectedValue),
                     )))
                 }
             };
@@ -247,4 +308,93 @@ fn temporary_prelude(env: &EnvironmentRef) {
 
     math!(*, multiplication_precedence_group);
     math!(/, multiplication_precedence_group);
+
+    // Comparisons
+
+    fn equal(left: &Value, right: &Value, env: &EnvironmentRef, stack: &Stack) -> Result<bool> {
+        let stack = stack.add(|| {
+            format!(
+                "Comparing '{}' and '{}'",
+                left.try_format(env, stack),
+                right.try_format(env, stack)
+            )
+        });
+
+        // Only compare values of the same kind, since most values can be
+        // converted to text
+        macro_rules! compare {
+            ($primitive:ty, $id:ident, $field:tt) => {
+                if left.has_trait_directly(TraitID::$id())
+                    && right.has_trait_directly(TraitID::$id())
+                {
+                    let left = left.get_primitive::<$primitive>(env, &stack)?;
+                    let right = right.get_primitive::<$primitive>(env, &stack)?;
+
+                    return Ok(left.$field == right.$field);
+                }
+            };
+        }
+
+        compare!(Number, number, number);
+        compare!(Text, text, text);
+        compare!(Boolean, boolean, 0);
+
+        Err(ReturnState::Error(Box::new(
+            Error::new(
+                "Can only compare two numbers, two pieces of text or two booleans",
+                &stack,
+            )
+            .with_code(ErrorCode::InvalidComparison),
+        )))
+    }
+
+    macro_rules! comparison {
+        ($name:expr, $precedence_group:ident, |$left:ident, $right:ident, $env:ident, $stack:ident| $compare:expr) => {{
+            let operator = Operator::collect(|$left, $right, $env, $stack| {
+                let $left = $left.evaluate($env, $stack)?;
+                let $right = $right.evaluate($env, $stack)?;
+
+                Ok(Value::of(Boolean($compare)))
+            });
+
+            add_operator(&operator, &$precedence_group);
+
+            env.borrow_mut().set_variable($name, Value::of(operator));
+        }};
+    }
+
+    macro_rules! order {
+        ($operation:tt, $precedence_group:ident) => {
+            comparison!(stringify!($operation), $precedence_group, |left, right, env, stack| {
+                let left = left.get_primitive::<Number>(env, stack)?;
+                let right = right.get_primitive::<Number>(env, stack)?;
+
+                left.number $operation right.number
+            })
+        };
+    }
+
+    // Comparisons are grouped outside of math, so 'a + 1 = b' compares 'a + 1'
+    // with 'b'
+    let comparison_precedence_group = add_precedence_group(
+        Associativity::Left,
+        PrecedenceGroupComparison::higher_than(multiplication_precedence_group),
+    );
+
+    comparison!(
+        "=",
+        comparison_precedence_group,
+        |left, right, env, stack| equal(&left, &right, env, stack)?
+    );
+
+    comparison!(
+        "/=",
+        comparison_precedence_group,
+        |left, right, env, stack| !equal(&left, &right, env, stack)?
+    );
+
+    order!(<, comparison_precedence_group);
+    order!(>, comparison_precedence_group);
+    order!(<=, comparison_precedence_group);
+    order!(>=, comparison_precedence_group);
 }
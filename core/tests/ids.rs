@@ -0,0 +1,37 @@
+use wipple::*;
+
+/// Create a trait using 'trait', like 'Person : trait empty' does.
+fn new_trait() -> TraitID {
+    let env = Environment::global();
+    let stack = Stack::new();
+
+    let r#trait = Name::new("trait").resolve(&env, &stack).unwrap();
+
+    r#trait
+        .call(&Value::of(Name::new("empty")), &env, &stack)
+        .unwrap()
+        .get_primitive::<TraitConstructor>(&env, &stack)
+        .unwrap()
+        .id
+}
+
+#[test]
+fn programs_get_the_same_ids_every_time_they_run() {
+    wipple::setup();
+    let first_run = (new_trait(), new_trait());
+
+    wipple::setup();
+    let second_run = (new_trait(), new_trait());
+
+    assert_eq!(first_run, second_run);
+    assert_ne!(first_run.0, first_run.1);
+}
+
+#[test]
+fn traits_from_earlier_runs_do_not_name_new_traits() {
+    wipple::setup();
+    new_trait().register_name("Person");
+
+    wipple::setup();
+    assert_eq!(new_trait().display_name(), None);
+}
@@ -0,0 +1,115 @@
+use wipple_parser::*;
+
+/// A variable assigned using 'name : value'.
+pub struct Assignment<'a> {
+    pub name: &'a str,
+    pub name_location: &'a SourceLocation,
+    pub statement: &'a AstNodeStatement,
+}
+
+impl<'a> Assignment<'a> {
+    /// Whether the value is written as a closure, eg. 'f : x -> x'.
+    pub fn is_function(&self) -> bool {
+        self.statement.items[2..]
+            .iter()
+            .any(|item| matches!(&item.node, AstNode::Name(name) if name == "->"))
+    }
+}
+
+fn statements(ast: &Ast) -> &[AstNodeStatement] {
+    match &ast.node {
+        AstNode::Block(statements) | AstNode::Module(statements) => statements,
+        _ => &[],
+    }
+}
+
+fn children(ast: &Ast) -> Vec<&Ast> {
+    match &ast.node {
+        AstNode::Block(statements) | AstNode::Module(statements) => statements
+            .iter()
+            .flat_map(|statement| &statement.items)
+            .collect(),
+        AstNode::List(items) => items.iter().collect(),
+        AstNode::Quoted(value) => vec![value],
+        _ => Vec::new(),
+    }
+}
+
+fn assignment(statement: &AstNodeStatement) -> Option<Assignment<'_>> {
+    match statement.items.as_slice() {
+        [name, colon, _, ..] => match (&name.node, &colon.node) {
+            (AstNode::Name(name_text), AstNode::Name(colon)) if colon == ":" => Some(Assignment {
+                name: name_text,
+                name_location: &name.location,
+                statement,
+            }),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The variables assigned at the top level of a file.
+pub fn top_level_assignments(ast: &Ast) -> Vec<Assignment<'_>> {
+    statements(ast).iter().filter_map(assignment).collect()
+}
+
+/// Every variable assigned in a file, including inside blocks and modules.
+pub fn all_assignments(ast: &Ast) -> Vec<Assignment<'_>> {
+    fn collect<'a>(ast: &'a Ast, assignments: &mut Vec<Assignment<'a>>) {
+        assignments.extend(statements(ast).iter().filter_map(assignment));
+
+        for child in children(ast) {
+            collect(child, assignments);
+        }
+    }
+
+    let mut assignments = Vec::new();
+    collect(ast, &mut assignments);
+    assignments
+}
+
+/// Find the name at a byte offset, including when the offset is just after the
+/// end of the name.
+pub fn name_at(ast: &Ast, offset: usize) -> Option<(&str, &SourceLocation)> {
+    if let AstNode::Name(name) = &ast.node {
+        return (ast.location.start..=ast.location.end)
+            .contains(&offset)
+            .then_some((name.as_str(), &ast.location));
+    }
+
+    children(ast)
+        .into_iter()
+        .filter(|child| (child.location.start..=child.location.end).contains(&offset))
+        .find_map(|child| name_at(child, offset))
+}
+
+/// The module names passed to 'import' or 'use', eg. 'import "./utils"'.
+pub fn imports(ast: &Ast) -> Vec<&str> {
+    let mut imports = Vec::new();
+
+    let sequences = match &ast.node {
+        AstNode::Block(statements) | AstNode::Module(statements) => statements
+            .iter()
+            .map(|statement| statement.items.as_slice())
+            .collect(),
+        AstNode::List(items) => vec![items.as_slice()],
+        _ => Vec::new(),
+    };
+
+    for pair in sequences.into_iter().flat_map(|items| items.windows(2)) {
+        if let (AstNode::Name(function), AstNode::Text(module_name)) =
+            (&pair[0].node, &pair[1].node)
+        {
+            if function == "import" || function == "use" {
+                imports.push(module_name.as_str());
+            }
+        }
+    }
+
+    for child in children(ast) {
+        imports.extend(self::imports(child));
+    }
+
+    imports
+}
@@ -0,0 +1,89 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use wipple::*;
+use wipple_parser::Ast;
+
+pub fn setup() {
+    wipple::setup();
+    wipple_projects::setup();
+
+    // Standard output is used to talk to the editor, so programs evaluated to
+    // provide hovers can't print anything
+    *Environment::global().borrow_mut().show() = ShowFn::new(|_, _, _| Ok(()));
+}
+
+/// Find the root of the project containing a file: the closest folder with a
+/// 'project.wpl' file, or the file's own folder.
+pub fn project_root(path: &Path) -> Option<PathBuf> {
+    let folder = path.parent()?;
+
+    Some(
+        folder
+            .ancestors()
+            .find(|folder| folder.join("project.wpl").is_file())
+            .unwrap_or(folder)
+            .to_path_buf(),
+    )
+}
+
+/// How long a file can be evaluated for to describe one of its variables.
+const HOVER_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub fn stack_for_file(path: &Path) -> Stack {
+    let mut stack = Stack::new();
+
+    // The server runs on a thread started by 'run_on_large_stack'
+    stack.max_depth = LARGE_STACK_MAX_DEPTH;
+
+    // Files are evaluated whenever the editor asks for a hover, so they can
+    // only use dependencies that were installed by running the project
+    stack.can_install_dependencies = false;
+
+    stack.current_file = Some(path.to_path_buf());
+    stack.project_root = project_root(path);
+    stack
+}
+
+/// Evaluate a file and describe the value of one of its variables, eg.
+/// 'no-op : <closure 'no-op'>', along with its documentation.
+pub fn describe_variable(
+    ast: &Ast,
+    path: &Path,
+    name: &str,
+) -> Result<Option<(String, Option<String>)>> {
+    let mut stack = stack_for_file(path);
+
+    // Files are evaluated as they're being edited, so a loop that never ends
+    // mustn't stop the server from responding
+    stack.budget = Budget::new().with_timeout(HOVER_TIMEOUT);
+
+    // Start from a new global environment, so the result doesn't depend on
+    // what was evaluated before and imported files that changed since they
+    // were last evaluated are imported again
+    setup();
+
+    let env = Environment::child_of(&Environment::global()).into_ref();
+    let program = wipple_parser::convert(ast, Some(path));
+
+    let module = program.evaluate(&env, &stack)?.get_primitive_or::<Module>(
+        "Expected a module",
+        &env,
+        &stack,
+    )?;
+
+    let value = match Name::new(name).resolve_without_computing_if_present(&module.env) {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+
+    let documentation = value
+        .get_primitive_if_present::<Documentation>(&module.env, &stack)?
+        .map(|documentation| documentation.text);
+
+    Ok(Some((
+        format!("{} : {}", name, value.try_format(&module.env, &stack)),
+        documentation,
+    )))
+}
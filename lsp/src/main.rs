@@ -0,0 +1,38 @@
+mod analysis;
+mod evaluate;
+mod position;
+mod server;
+
+use lsp_server::Connection;
+use lsp_types::{
+    HoverProviderCapability, OneOf, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind,
+};
+
+fn main() -> server::Result {
+    // Hovers evaluate programs, which need a larger stack than the main
+    // thread's to recurse deeply
+    wipple::run_on_large_stack(run)
+}
+
+fn run() -> server::Result {
+    evaluate::setup();
+
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        ..Default::default()
+    };
+
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    server::Server::new(connection).run()?;
+
+    io_threads.join()?;
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use lsp_types::{Position, Range};
+
+// LSP positions count columns in UTF-16 code units, while the parser reports
+// byte offsets
+
+/// Convert a byte offset in the code into an LSP position.
+pub fn position(code: &str, offset: usize) -> Position {
+    let offset = offset.min(code.len());
+    let before = &code[..offset];
+
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+    let character = before[line_start..].encode_utf16().count();
+
+    Position::new(line as u32, character as u32)
+}
+
+/// Convert a span of byte offsets into an LSP range.
+pub fn range(code: &str, start: usize, end: usize) -> Range {
+    Range::new(position(code, start), position(code, end))
+}
+
+/// Convert an LSP position into a byte offset in the code, clamping positions
+/// past the end of a line to the end of that line.
+pub fn offset(code: &str, position: Position) -> usize {
+    let line_start = code
+        .split_inclusive('\n')
+        .take(position.line as usize)
+        .map(str::len)
+        .sum::<usize>();
+
+    let mut units = 0;
+
+    for (index, c) in code[line_start..].char_indices() {
+        if c == '\n' || units >= position.character as usize {
+            return line_start + index;
+        }
+
+        units += c.len_utf16();
+    }
+
+    code.len()
+}
@@ -0,0 +1,329 @@
+use crate::{analysis::*, evaluate::*, position::*};
+use lsp_server::{Connection, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as NotificationTrait, PublishDiagnostics,
+    },
+    request::{DocumentSymbolRequest, GotoDefinition, HoverRequest, Request as RequestTrait},
+    Diagnostic, DiagnosticSeverity, DocumentSymbol, DocumentSymbolResponse, GotoDefinitionResponse,
+    Hover, HoverContents, Location, MarkupContent, MarkupKind, PublishDiagnosticsParams,
+    SymbolKind, TextDocumentPositionParams, Url,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+use wipple_parser::{parse_file_recovering, Ast};
+
+pub type Result<T = ()> = std::result::Result<T, Box<dyn Error + Sync + Send>>;
+
+pub struct Server {
+    connection: Connection,
+    documents: HashMap<Url, String>,
+}
+
+impl Server {
+    pub fn new(connection: Connection) -> Self {
+        Server {
+            connection,
+            documents: HashMap::new(),
+        }
+    }
+
+    pub fn run(&mut self) -> Result {
+        while let Ok(message) = self.connection.receiver.recv() {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        break;
+                    }
+
+                    self.handle_request(request)?;
+                }
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                Message::Response(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(&mut self, request: Request) -> Result {
+        let id = request.id.clone();
+
+        let result = match request.method.as_str() {
+            DocumentSymbolRequest::METHOD => {
+                let params = params::<DocumentSymbolRequest>(request)?;
+                serde_json::to_value(self.document_symbols(&params.text_document.uri))?
+            }
+            GotoDefinition::METHOD => {
+                let params = params::<GotoDefinition>(request)?;
+                serde_json::to_value(self.definition(&params.text_document_position_params))?
+            }
+            HoverRequest::METHOD => {
+                let params = params::<HoverRequest>(request)?;
+                serde_json::to_value(self.hover(&params.text_document_position_params))?
+            }
+            _ => {
+                return self.respond(Response::new_err(
+                    id,
+                    lsp_server::ErrorCode::MethodNotFound as i32,
+                    format!("Unsupported request '{}'", request.method),
+                ))
+            }
+        };
+
+        self.respond(Response::new_ok(id, result))
+    }
+
+    fn respond(&self, response: Response) -> Result {
+        self.connection.sender.send(Message::Response(response))?;
+        Ok(())
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params = notification_params::<DidOpenTextDocument>(notification)?;
+
+                self.documents
+                    .insert(params.text_document.uri.clone(), params.text_document.text);
+
+                self.publish_diagnostics(&params.text_document.uri)?;
+            }
+            DidChangeTextDocument::METHOD => {
+                let params = notification_params::<DidChangeTextDocument>(notification)?;
+
+                // The server asks for the full text on every change
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    self.documents
+                        .insert(params.text_document.uri.clone(), change.text);
+                }
+
+                self.publish_diagnostics(&params.text_document.uri)?;
+            }
+            DidCloseTextDocument::METHOD => {
+                let params = notification_params::<DidCloseTextDocument>(notification)?;
+                self.documents.remove(&params.text_document.uri);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn publish_diagnostics(&self, uri: &Url) -> Result {
+        let code = match self.documents.get(uri) {
+            Some(code) => code,
+            None => return Ok(()),
+        };
+
+        let (_, errors) = parse_file_recovering(code);
+
+        let diagnostics = errors
+            .into_iter()
+            .map(|error| {
+                // Underline the character the error points to, if there is one
+                let end = code[error.offset..]
+                    .chars()
+                    .next()
+                    .filter(|&c| c != '\n')
+                    .map(|c| error.offset + c.len_utf8())
+                    .unwrap_or(error.offset);
+
+                Diagnostic {
+                    range: range(code, error.offset, end),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    source: Some(String::from("wipple")),
+                    message: error.message,
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        let params = PublishDiagnosticsParams::new(uri.clone(), diagnostics, None);
+
+        self.connection
+            .sender
+            .send(Message::Notification(Notification::new(
+                String::from(PublishDiagnostics::METHOD),
+                params,
+            )))?;
+
+        Ok(())
+    }
+
+    fn document_symbols(&self, uri: &Url) -> Option<DocumentSymbolResponse> {
+        let code = self.documents.get(uri)?;
+        let (ast, _) = parse_file_recovering(code);
+
+        let symbols = top_level_assignments(&ast)
+            .into_iter()
+            .map(|assignment| {
+                #[allow(deprecated)] // 'deprecated' must still be provided
+                DocumentSymbol {
+                    name: String::from(assignment.name),
+                    detail: None,
+                    kind: if assignment.is_function() {
+                        SymbolKind::FUNCTION
+                    } else {
+                        SymbolKind::VARIABLE
+                    },
+                    tags: None,
+                    deprecated: None,
+                    range: range(
+                        code,
+                        assignment.statement.location.start,
+                        assignment.statement.location.end,
+                    ),
+                    selection_range: range(
+                        code,
+                        assignment.name_location.start,
+                        assignment.name_location.end,
+                    ),
+                    children: None,
+                }
+            })
+            .collect();
+
+        Some(DocumentSymbolResponse::Nested(symbols))
+    }
+
+    fn definition(&self, position: &TextDocumentPositionParams) -> Option<GotoDefinitionResponse> {
+        let uri = &position.text_document.uri;
+        let code = self.documents.get(uri)?;
+        let (ast, _) = parse_file_recovering(code);
+
+        let (name, _) = name_at(&ast, offset(code, position.position))?;
+
+        // Prefer variables assigned in the same file
+        let locations = all_assignments(&ast)
+            .into_iter()
+            .filter(|assignment| assignment.name == name)
+            .map(|assignment| {
+                Location::new(
+                    uri.clone(),
+                    range(
+                        code,
+                        assignment.name_location.start,
+                        assignment.name_location.end,
+                    ),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if !locations.is_empty() {
+            return Some(GotoDefinitionResponse::Array(locations));
+        }
+
+        let path = uri.to_file_path().ok()?;
+        let mut locations = Vec::new();
+        let mut visited = HashSet::new();
+        self.find_in_imports(&ast, &path, name, &mut visited, &mut locations);
+
+        if locations.is_empty() {
+            None
+        } else {
+            Some(GotoDefinitionResponse::Array(locations))
+        }
+    }
+
+    /// Search the files imported by a file (and the files they import) for
+    /// top-level variables with the name.
+    fn find_in_imports(
+        &self,
+        ast: &Ast,
+        path: &Path,
+        name: &str,
+        visited: &mut HashSet<PathBuf>,
+        locations: &mut Vec<Location>,
+    ) {
+        let stack = stack_for_file(path);
+
+        for module_name in imports(ast) {
+            let imported_path = match wipple_projects::resolve(module_name, &stack) {
+                Ok(path) if path.is_file() => path,
+                _ => continue,
+            };
+
+            if !visited.insert(imported_path.clone()) {
+                continue;
+            }
+
+            let uri = match Url::from_file_path(&imported_path) {
+                Ok(uri) => uri,
+                Err(_) => continue,
+            };
+
+            // Use the contents of the editor if the file is open
+            let code = match self.documents.get(&uri) {
+                Some(code) => code.clone(),
+                None => match fs::read_to_string(&imported_path) {
+                    Ok(code) => code,
+                    Err(_) => continue,
+                },
+            };
+
+            let (imported_ast, _) = parse_file_recovering(&code);
+
+            locations.extend(
+                top_level_assignments(&imported_ast)
+                    .into_iter()
+                    .filter(|assignment| assignment.name == name)
+                    .map(|assignment| {
+                        Location::new(
+                            uri.clone(),
+                            range(
+                                &code,
+                                assignment.name_location.start,
+                                assignment.name_location.end,
+                            ),
+                        )
+                    }),
+            );
+
+            self.find_in_imports(&imported_ast, &imported_path, name, visited, locations);
+        }
+    }
+
+    fn hover(&self, position: &TextDocumentPositionParams) -> Option<Hover> {
+        let uri = &position.text_document.uri;
+        let code = self.documents.get(uri)?;
+        let (ast, errors) = parse_file_recovering(code);
+
+        let (name, location) = name_at(&ast, offset(code, position.position))?;
+
+        // Only evaluate code that parses, since errors would stop it anyway
+        if !errors.is_empty() {
+            return None;
+        }
+
+        let path = uri.to_file_path().ok()?;
+        let (description, documentation) = describe_variable(&ast, &path, name).ok()??;
+
+        let mut value = format!("```wipple\n{}\n```", description);
+        if let Some(documentation) = documentation {
+            value.push_str("\n\n");
+            value.push_str(&documentation);
+        }
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(range(code, location.start, location.end)),
+        })
+    }
+}
+
+fn params<R: RequestTrait>(request: Request) -> Result<R::Params> {
+    Ok(request.extract::<R::Params>(R::METHOD)?.1)
+}
+
+fn notification_params<N: NotificationTrait>(notification: Notification) -> Result<N::Params> {
+    Ok(notification.extract::<N::Params>(N::METHOD)?)
+}
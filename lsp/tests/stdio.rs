@@ -0,0 +1,339 @@
+use lsp_server::{Message, Notification, Request, RequestId};
+use serde_json::{json, Value};
+use std::{
+    fs,
+    io::BufReader,
+    path::Path,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    time::{Duration, Instant},
+};
+
+/// Drives the language server over stdio, like an editor would.
+struct Client {
+    process: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: i32,
+}
+
+impl Client {
+    fn start() -> Self {
+        let mut process = Command::new(env!("CARGO_BIN_EXE_wipple_lsp"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("failed to start the language server");
+
+        let stdin = process.stdin.take().unwrap();
+        let stdout = BufReader::new(process.stdout.take().unwrap());
+
+        Client {
+            process,
+            stdin,
+            stdout,
+            next_id: 0,
+        }
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        self.next_id += 1;
+        let id = RequestId::from(self.next_id);
+
+        Message::Request(Request::new(id.clone(), String::from(method), params))
+            .write(&mut self.stdin)
+            .unwrap();
+
+        loop {
+            match self.receive() {
+                Message::Response(response) if response.id == id => {
+                    assert!(response.error.is_none(), "{:?}", response.error);
+                    return response.result.unwrap_or(Value::Null);
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        Message::Notification(Notification::new(String::from(method), params))
+            .write(&mut self.stdin)
+            .unwrap();
+    }
+
+    fn receive(&mut self) -> Message {
+        Message::read(&mut self.stdout)
+            .unwrap()
+            .expect("the language server closed its output")
+    }
+
+    fn diagnostics(&mut self) -> Value {
+        loop {
+            if let Message::Notification(notification) = self.receive() {
+                if notification.method == "textDocument/publishDiagnostics" {
+                    return notification.params["diagnostics"].clone();
+                }
+            }
+        }
+    }
+
+    fn stop(mut self) {
+        self.request("shutdown", Value::Null);
+        self.notify("exit", Value::Null);
+
+        assert!(self.process.wait().unwrap().success());
+    }
+}
+
+fn uri(path: &Path) -> String {
+    format!("file://{}", path.to_string_lossy())
+}
+
+fn position(uri: &str, line: u32, character: u32) -> Value {
+    json!({
+        "textDocument": { "uri": uri },
+        "position": { "line": line, "character": character },
+    })
+}
+
+#[test]
+fn scripted_session() {
+    let folder = std::env::temp_dir().join(format!("wipple-lsp-test-{}", std::process::id()));
+    fs::create_dir_all(&folder).unwrap();
+    fs::write(folder.join("utils.wpl"), "greeting : \"hello\"\n").unwrap();
+
+    let main_uri = uri(&folder.join("main.wpl"));
+    let utils_uri = uri(&folder.join("utils.wpl"));
+
+    let mut client = Client::start();
+
+    let result = client.request("initialize", json!({ "capabilities": {} }));
+    assert_eq!(result["capabilities"]["hoverProvider"], json!(true));
+    client.notify("initialized", json!({}));
+
+    // Syntax errors are reported when a document is opened...
+    client.notify(
+        "textDocument/didOpen",
+        json!({
+            "textDocument": {
+                "uri": main_uri,
+                "languageId": "wipple",
+                "version": 1,
+                "text": "x )\ny : \"abc\n",
+            }
+        }),
+    );
+
+    let diagnostics = client.diagnostics();
+    assert_eq!(diagnostics.as_array().unwrap().len(), 2);
+    assert_eq!(
+        diagnostics[0]["message"],
+        json!("unexpected `)` without a matching opening bracket")
+    );
+    assert_eq!(
+        diagnostics[1]["message"],
+        json!("unterminated text starting at 2:5")
+    );
+
+    // ...and cleared once they're fixed
+    let code = "use \"./utils\"\nf : x -> x\nn : 42\nm : n\nshow greeting\n--- The identity function\ng : f\n";
+
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": main_uri, "version": 2 },
+            "contentChanges": [{ "text": code }],
+        }),
+    );
+
+    assert_eq!(client.diagnostics(), json!([]));
+
+    let symbols = client.request(
+        "textDocument/documentSymbol",
+        json!({ "textDocument": { "uri": main_uri } }),
+    );
+
+    let symbols = symbols
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|symbol| (symbol["name"].clone(), symbol["kind"].clone()))
+        .collect::<Vec<_>>();
+
+    // Functions are kind 12, variables are kind 13
+    assert_eq!(
+        symbols,
+        vec![
+            (json!("f"), json!(12)),
+            (json!("n"), json!(13)),
+            (json!("m"), json!(13)),
+            (json!("g"), json!(13))
+        ]
+    );
+
+    // Definitions in the same file
+    let definition = client.request("textDocument/definition", position(&main_uri, 3, 4));
+    assert_eq!(definition[0]["uri"], json!(main_uri));
+    assert_eq!(
+        definition[0]["range"]["start"],
+        json!({ "line": 2, "character": 0 })
+    );
+
+    // Definitions in an imported file
+    let definition = client.request("textDocument/definition", position(&main_uri, 4, 6));
+    assert_eq!(definition[0]["uri"], json!(utils_uri));
+    assert_eq!(
+        definition[0]["range"]["start"],
+        json!({ "line": 0, "character": 0 })
+    );
+
+    let hover = client.request("textDocument/hover", position(&main_uri, 2, 0));
+    assert_eq!(hover["contents"]["value"], json!("```wipple\nn : 42\n```"));
+
+    let hover = client.request("textDocument/hover", position(&main_uri, 1, 0));
+    assert_eq!(
+        hover["contents"]["value"],
+        json!("```wipple\nf : <closure 'f'>\n```")
+    );
+
+    // Documentation is shown below the value
+    let hover = client.request("textDocument/hover", position(&main_uri, 6, 0));
+    assert_eq!(
+        hover["contents"]["value"],
+        json!("```wipple\ng : <closure 'f'>\n```\n\nThe identity function")
+    );
+
+    client.stop();
+
+    fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn hovers_stop_programs_that_never_finish() {
+    let folder =
+        std::env::temp_dir().join(format!("wipple-lsp-timeout-test-{}", std::process::id()));
+    fs::create_dir_all(&folder).unwrap();
+
+    let main_uri = uri(&folder.join("main.wpl"));
+
+    let mut client = Client::start();
+
+    client.request("initialize", json!({ "capabilities": {} }));
+    client.notify("initialized", json!({}));
+
+    client.notify(
+        "textDocument/didOpen",
+        json!({
+            "textDocument": {
+                "uri": main_uri,
+                "languageId": "wipple",
+                "version": 1,
+                "text": "n : 42\nloop [\n    x : 1\n]\n",
+            }
+        }),
+    );
+
+    assert_eq!(client.diagnostics(), json!([]));
+
+    // The file never finishes, so there's no value to show...
+    let start = Instant::now();
+    let hover = client.request("textDocument/hover", position(&main_uri, 0, 0));
+    assert_eq!(hover, Value::Null);
+    assert!(start.elapsed() < Duration::from_secs(10));
+
+    // ...but the server keeps responding
+    let symbols = client.request(
+        "textDocument/documentSymbol",
+        json!({ "textDocument": { "uri": main_uri } }),
+    );
+    assert_eq!(symbols[0]["name"], json!("n"));
+
+    client.stop();
+
+    fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn hovers_evaluate_recursive_programs() {
+    let folder =
+        std::env::temp_dir().join(format!("wipple-lsp-recursion-test-{}", std::process::id()));
+    fs::create_dir_all(&folder).unwrap();
+
+    let main_uri = uri(&folder.join("main.wpl"));
+
+    let mut client = Client::start();
+
+    client.request("initialize", json!({ "capabilities": {} }));
+    client.notify("initialized", json!({}));
+
+    client.notify(
+        "textDocument/didOpen",
+        json!({
+            "textDocument": {
+                "uri": main_uri,
+                "languageId": "wipple",
+                "version": 1,
+                "text": "count : n -> if (n = 0) 0 (1 + (count (n - 1)))\nn : count 100\n",
+            }
+        }),
+    );
+
+    assert_eq!(client.diagnostics(), json!([]));
+
+    let hover = client.request("textDocument/hover", position(&main_uri, 1, 0));
+    assert_eq!(hover["contents"]["value"], json!("```wipple\nn : 100\n```"));
+
+    client.stop();
+
+    fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn hovers_do_not_install_dependencies() {
+    let folder = std::env::temp_dir().join(format!(
+        "wipple-lsp-dependencies-test-{}",
+        std::process::id()
+    ));
+    let project = folder.join("project");
+    fs::create_dir_all(project.join("utils")).unwrap();
+    fs::write(project.join("utils/utils.wpl"), "x : 1\n").unwrap();
+    fs::write(
+        project.join("project.wpl"),
+        "main : \"main\"\ndependencies : {\n    utils : path \"utils\"\n}\n",
+    )
+    .unwrap();
+    fs::write(project.join("main.wpl"), "x : 2\n").unwrap();
+
+    let main_uri = uri(&folder.join("main.wpl"));
+
+    let mut client = Client::start();
+
+    client.request("initialize", json!({ "capabilities": {} }));
+    client.notify("initialized", json!({}));
+
+    client.notify(
+        "textDocument/didOpen",
+        json!({
+            "textDocument": {
+                "uri": main_uri,
+                "languageId": "wipple",
+                "version": 1,
+                "text": "n : 42\nuse \"./project\"\n",
+            }
+        }),
+    );
+
+    assert_eq!(client.diagnostics(), json!([]));
+
+    // The project's dependencies were never installed, so the file can't be
+    // evaluated...
+    let hover = client.request("textDocument/hover", position(&main_uri, 0, 0));
+    assert_eq!(hover, Value::Null);
+
+    // ...and hovering doesn't install them
+    assert!(!project.join(".wipple").exists());
+    assert!(!project.join("project.lock").exists());
+
+    client.stop();
+
+    fs::remove_dir_all(&folder).unwrap();
+}
@@ -11,14 +11,7 @@ pub fn convert(ast: &Ast, file: Option<&Path>) -> Value {
         Block(statements) => Value::of(wipple::Block::new_located(
             &statements
                 .iter()
-                .map(|statement| wipple::List {
-                    items: statement
-                        .items
-                        .iter()
-                        .map(|node| convert(node, file))
-                        .collect(),
-                    location: Some(location(&statement.location, file)),
-                })
+                .map(|statement| convert_statement(statement, file))
                 .collect::<Vec<_>>(),
             Some(location(&ast.location, file)),
         )),
@@ -26,14 +19,7 @@ pub fn convert(ast: &Ast, file: Option<&Path>) -> Value {
         Module(statements) => Value::of(wipple::ModuleBlock::new_located(
             &statements
                 .iter()
-                .map(|statement| wipple::List {
-                    items: statement
-                        .items
-                        .iter()
-                        .map(|node| convert(node, file))
-                        .collect(),
-                    location: Some(location(&statement.location, file)),
-                })
+                .map(|statement| convert_statement(statement, file))
                 .collect::<Vec<_>>(),
             Some(location(&ast.location, file)),
         )),
@@ -65,6 +51,46 @@ pub fn convert(ast: &Ast, file: Option<&Path>) -> Value {
             text,
             Some(location(&ast.location, file)),
         )),
+
+        // Code containing syntax errors can still be converted (eg. to provide
+        // editor features), but fails when it's evaluated
+        Error { message, .. } => {
+            let message = format!("Syntax error: {}", message);
+            let location = location(&ast.location, file);
+
+            Value::of(EvaluateFn::new(move |_, stack| {
+                let stack =
+                    stack.add_location(|| String::from("Evaluating invalid code"), &location);
+
+                Err(ReturnState::Error(Box::new(
+                    wipple::Error::new(&message, &stack).with_code(ErrorCode::SyntaxError),
+                )))
+            }))
+        }
+    }
+}
+
+fn convert_statement(statement: &AstNodeStatement, file: Option<&Path>) -> wipple::List {
+    let mut items = statement
+        .items
+        .iter()
+        .map(|node| convert(node, file))
+        .collect::<Vec<_>>();
+
+    // Documentation is attached to the name being assigned to, so it can be
+    // added to the variable in 'set_variable_with_documentation'
+    if let (Some(documentation), Some(first)) = (&statement.documentation, items.first_mut()) {
+        if matches!(statement.items[0].node, AstNode::Name(_)) {
+            *first = first.add(&Trait::of_primitive(wipple::Documentation {
+                text: documentation.clone(),
+            }));
+        }
+    }
+
+    wipple::List {
+        items,
+        location: Some(location(&statement.location, file)),
+        allocation: None,
     }
 }
 
@@ -73,5 +99,7 @@ fn location(location: &crate::SourceLocation, file: Option<&Path>) -> wipple::So
         file: file.map(|path| path.to_path_buf()),
         line: location.line,
         column: location.column,
+        start: location.start,
+        end: location.end,
     }
 }
@@ -0,0 +1,204 @@
+use crate::*;
+use std::{collections::BTreeSet, path::Path};
+
+/// Convert a byte offset into a 1-indexed line and column.
+fn line_column(code: &str, offset: usize) -> (usize, usize) {
+    let before = &code[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+
+    (line, column)
+}
+
+/// Describe the tokens the parser expected in human terms.
+fn humanize_expected<'a>(tokens: impl Iterator<Item = &'a str>) -> Vec<String> {
+    tokens
+        .filter_map(|token| {
+            Some(String::from(match token {
+                // Whitespace and comments are allowed almost everywhere, so
+                // listing them isn't helpful
+                "\" \"" | "\"\\t\"" | "\"--\"" => return None,
+                "\"\\n\"" | "\"\\r\"" | "\"\\r\\n\"" => "a new line",
+                "\")\"" => "`)`",
+                "\"]\"" => "`]`",
+                "\"}\"" => "`}`",
+                "EOF" => "the end of the file",
+                _ => "a value",
+            }))
+        })
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn describe_found(code: &str, offset: usize) -> String {
+    if offset == code.trim_end().len() {
+        return String::from("the end of the file");
+    }
+
+    match code[offset..].chars().next() {
+        None => String::from("the end of the file"),
+        Some('\n') | Some('\r') => String::from("a new line"),
+        Some(c) => format!("`{}`", c),
+    }
+}
+
+fn list(items: &[String]) -> String {
+    match items {
+        [] => String::from("nothing"),
+        [item] => item.clone(),
+        [items @ .., last] => format!("{} or {}", items.join(", "), last),
+    }
+}
+
+impl Error {
+    pub(crate) fn from_parse_error<'a>(
+        code: &str,
+        offset: usize,
+        expected: impl Iterator<Item = &'a str>,
+    ) -> Self {
+        // The parser runs on the code with a newline appended, so errors at the
+        // very end are moved back onto the last line
+        let offset = offset.min(code.trim_end().len());
+        let (line, column) = line_column(code, offset);
+
+        let mut expected = humanize_expected(expected);
+        let scan = scan(&code[..offset]);
+
+        let format_location = |offset| {
+            let (line, column) = line_column(code, offset);
+            format!("{}:{}", line, column)
+        };
+
+        let message = if let Some(text) = scan.unterminated_text {
+            // Any character can appear inside text, so only the closing quote
+            // is worth mentioning
+            expected = vec![String::from("a closing `\"`")];
+
+            format!("unterminated text starting at {}", format_location(text))
+        } else if let Some(bracket) = scan.unmatched {
+            format!(
+                "unexpected `{}` without a matching opening bracket",
+                bracket.bracket
+            )
+        } else {
+            let found = describe_found(code, offset);
+            let found_closing_bracket =
+                matches!(code[offset..].chars().next(), Some(')' | ']' | '}'));
+
+            match scan.unclosed.last() {
+                Some(bracket)
+                    if (found_closing_bracket
+                        && !code[offset..].starts_with(bracket.closing()))
+                        || offset == code.trim_end().len() =>
+                {
+                    format!(
+                        "unclosed `{}` opened at {}; expected `{}`, found {}",
+                        bracket.bracket,
+                        format_location(bracket.offset),
+                        bracket.closing(),
+                        found
+                    )
+                }
+                None if found_closing_bracket => {
+                    format!("unexpected {} without a matching opening bracket", found)
+                }
+                _ => format!("expected {}, found {}", list(&expected), found),
+            }
+        };
+
+        Error {
+            message,
+            line,
+            column,
+            offset,
+            expected,
+        }
+    }
+
+    /// Render the error with the line of code it occurred on, eg.
+    ///
+    /// ```text
+    /// Syntax error: unclosed `[` opened at 1:5; expected `]`, found the end of the file
+    ///  --> main.wpl:1:7
+    ///   |
+    /// 1 | a : [b
+    ///   |       ^ expected `]`, a new line or a value
+    /// ```
+    pub fn render(&self, code: &str, file: Option<&Path>) -> String {
+        let location = match file {
+            Some(file) => format!("{}:{}:{}", file.to_string_lossy(), self.line, self.column),
+            None => format!("{}:{}", self.line, self.column),
+        };
+
+        let source_line = code.lines().nth(self.line - 1).unwrap_or_default();
+        let gutter = " ".repeat(self.line.to_string().len());
+
+        let caret_indent = source_line
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+
+        let mut rendered = format!(
+            "Syntax error: {}\n{} --> {}\n{} |\n{} | {}\n{} | {}^",
+            self.message, gutter, location, gutter, self.line, source_line, gutter, caret_indent
+        );
+
+        if !self.expected.is_empty() {
+            rendered.push_str(&format!(" expected {}", list(&self.expected)));
+        }
+
+        rendered
+    }
+}
+
+impl Error {
+    /// Collect the error nodes produced by the recovering parser.
+    pub(crate) fn collect_from_ast(code: &str, ast: &Ast) -> Vec<Self> {
+        fn collect(code: &str, ast: &Ast, errors: &mut Vec<Error>) {
+            match &ast.node {
+                AstNode::Block(statements) | AstNode::Module(statements) => {
+                    for statement in statements {
+                        for item in &statement.items {
+                            collect(code, item, errors);
+                        }
+                    }
+                }
+                AstNode::List(items) => {
+                    for item in items {
+                        collect(code, item, errors);
+                    }
+                }
+                AstNode::Quoted(value) => collect(code, value, errors),
+                AstNode::Error { message, expected } => {
+                    let offset = ast.location.start.min(code.trim_end().len());
+                    let (line, column) = line_column(code, offset);
+
+                    errors.push(Error {
+                        message: message.clone(),
+                        line,
+                        column,
+                        offset,
+                        expected: expected.clone(),
+                    });
+                }
+                AstNode::Name(_) | AstNode::Text(_) | AstNode::Number(_) => {}
+            }
+        }
+
+        let mut errors = Vec::new();
+        collect(code, ast, &mut errors);
+        errors.sort_by_key(|error| error.offset);
+        errors
+    }
+}
+
+/// Render each error as in 'Error::render', separated by blank lines.
+pub fn render_errors(errors: &[Error], code: &str, file: Option<&Path>) -> String {
+    errors
+        .iter()
+        .map(|error| error.render(code, file))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
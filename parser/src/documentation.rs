@@ -0,0 +1,85 @@
+use crate::*;
+use std::collections::HashSet;
+
+/// Attach the '---' comments on the lines directly above each statement to
+/// the statement as its documentation.
+pub(crate) fn attach_documentation(ast: &mut Ast, code: &str) {
+    let doc_comments = comments(code)
+        .into_iter()
+        .filter(|comment| comment.text.starts_with("---") && on_own_line(code, comment.start))
+        .map(|comment| comment.start)
+        .collect::<HashSet<_>>();
+
+    attach_to_node(ast, code, &doc_comments);
+}
+
+fn attach_to_node(ast: &mut Ast, code: &str, doc_comments: &HashSet<usize>) {
+    match &mut ast.node {
+        AstNode::Block(statements) | AstNode::Module(statements) => {
+            for statement in statements {
+                statement.documentation =
+                    documentation_before(statement.location.start, code, doc_comments);
+
+                for item in &mut statement.items {
+                    attach_to_node(item, code, doc_comments);
+                }
+            }
+        }
+        AstNode::List(items) => {
+            for item in items {
+                attach_to_node(item, code, doc_comments);
+            }
+        }
+        AstNode::Quoted(value) => attach_to_node(value, code, doc_comments),
+        _ => {}
+    }
+}
+
+fn documentation_before(
+    offset: usize,
+    code: &str,
+    doc_comments: &HashSet<usize>,
+) -> Option<String> {
+    // Errors at the end of the file can be located after the last character
+    if offset > code.len() || !on_own_line(code, offset) {
+        return None;
+    }
+
+    let mut lines = Vec::new();
+    let mut start = line_start(code, offset);
+
+    // Walk upwards until a line isn't a doc comment
+    while start > 0 {
+        let previous_line_start = line_start(code, start - 1);
+        let line = &code[previous_line_start..start - 1];
+        let comment_start = previous_line_start + (line.len() - line.trim_start().len());
+
+        if !doc_comments.contains(&comment_start) {
+            break;
+        }
+
+        let text = line.trim()["---".len()..].trim_end();
+        lines.push(text.strip_prefix(' ').unwrap_or(text));
+
+        start = previous_line_start;
+    }
+
+    if lines.is_empty() {
+        return None;
+    }
+
+    lines.reverse();
+    Some(lines.join("\n"))
+}
+
+/// Whether only whitespace comes before the offset on its line.
+fn on_own_line(code: &str, offset: usize) -> bool {
+    code[line_start(code, offset)..offset].trim().is_empty()
+}
+
+fn line_start(code: &str, offset: usize) -> usize {
+    code[..offset]
+        .rfind('\n')
+        .map(|index| index + 1)
+        .unwrap_or(0)
+}
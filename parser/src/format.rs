@@ -0,0 +1,219 @@
+use crate::*;
+
+const INDENT: &str = "    ";
+
+/// Format a file, normalising whitespace, indentation and blank lines while
+/// keeping comments.
+pub fn format_file(code: &str) -> std::result::Result<String, Error> {
+    let ast = parse_file(code)?;
+
+    Ok(format(&ast, code))
+}
+
+/// Pretty-print an AST. The code the AST was parsed from is needed to keep
+/// comments and the original spelling of text and numbers.
+pub fn format(ast: &Ast, code: &str) -> String {
+    let formatter = Formatter {
+        code,
+        comments: comments(code),
+    };
+
+    match &ast.node {
+        AstNode::Block(statements) | AstNode::Module(statements) => {
+            formatter.statements(statements, 0, code.len(), 0)
+        }
+        _ => formatter.value(ast, 0) + "\n",
+    }
+}
+
+struct Formatter<'a> {
+    code: &'a str,
+    comments: Vec<Comment>,
+}
+
+enum Element<'a> {
+    Statement(&'a AstNodeStatement, Option<&'a Comment>),
+    Comment(&'a Comment),
+}
+
+impl<'a> Formatter<'a> {
+    /// Format statements on their own lines, along with the comments between
+    /// 'start' and 'end' that aren't inside the statements.
+    fn statements(
+        &'a self,
+        statements: &'a [AstNodeStatement],
+        start: usize,
+        end: usize,
+        indent: usize,
+    ) -> String {
+        let inside_statement = |offset: usize| {
+            statements.iter().any(|statement| {
+                (statement.location.start..statement.location.end).contains(&offset)
+            })
+        };
+
+        let mut elements = statements
+            .iter()
+            .map(|statement| {
+                (
+                    statement.location.start,
+                    Element::Statement(statement, None),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for comment in &self.comments {
+            if comment.start < start || comment.end > end || inside_statement(comment.start) {
+                continue;
+            }
+
+            // Comments on the same line as a statement stay at the end of it
+            let trailing = elements.iter_mut().find_map(|(_, element)| match element {
+                Element::Statement(statement, trailing @ None)
+                    if statement.location.end <= comment.start
+                        && !self.code[statement.location.end..comment.start].contains('\n') =>
+                {
+                    Some(trailing)
+                }
+                _ => None,
+            });
+
+            match trailing {
+                Some(trailing) => *trailing = Some(comment),
+                None => elements.push((comment.start, Element::Comment(comment))),
+            }
+        }
+
+        elements.sort_by_key(|(start, _)| *start);
+
+        let mut output = String::new();
+        let mut previous_end = None;
+
+        for (element_start, element) in elements {
+            // Keep up to one blank line between statements
+            if let Some(previous_end) = previous_end {
+                if self.code[previous_end..element_start].matches('\n').count() > 1 {
+                    output.push('\n');
+                }
+            }
+
+            output.push_str(&INDENT.repeat(indent));
+
+            previous_end = Some(match element {
+                Element::Statement(statement, trailing) => {
+                    output.push_str(&self.statement(statement, indent));
+
+                    match trailing {
+                        Some(comment) => {
+                            output.push(' ');
+                            output.push_str(&comment.text);
+                            comment.end
+                        }
+                        None => statement.location.end,
+                    }
+                }
+                Element::Comment(comment) => {
+                    output.push_str(&comment.text);
+                    comment.end
+                }
+            });
+
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn statement(&self, statement: &AstNodeStatement, indent: usize) -> String {
+        statement
+            .items
+            .iter()
+            .map(|item| self.value(item, indent))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn value(&self, ast: &Ast, indent: usize) -> String {
+        let source = &self.code[ast.location.start..ast.location.end];
+
+        match &ast.node {
+            AstNode::Name(name) => name.clone(),
+            // Keep escape sequences and trailing zeros as written
+            AstNode::Text(_) | AstNode::Number(_) => String::from(source),
+            AstNode::Quoted(value) => format!("'{}", self.value(value, indent)),
+            AstNode::List(items) => {
+                // Lists are written on one line, which would move comments
+                // inside them, so lists with comments are left as written
+                if self.has_comments_outside_blocks(ast) {
+                    return String::from(source);
+                }
+
+                format!(
+                    "({})",
+                    items
+                        .iter()
+                        .map(|item| self.value(item, indent))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                )
+            }
+            AstNode::Block(statements) => self.block("[", "]", statements, ast, indent),
+            AstNode::Module(statements) => self.block("{", "}", statements, ast, indent),
+            AstNode::Error { .. } => String::from(source),
+        }
+    }
+
+    fn block(
+        &self,
+        opening: &str,
+        closing: &str,
+        statements: &[AstNodeStatement],
+        ast: &Ast,
+        indent: usize,
+    ) -> String {
+        // Don't include the brackets themselves
+        let start = ast.location.start + 1;
+        let end = ast.location.end - 1;
+
+        let contents = self.statements(statements, start, end, indent + 1);
+
+        if contents.is_empty() {
+            format!("{}{}", opening, closing)
+        } else {
+            format!(
+                "{}\n{}{}{}",
+                opening,
+                contents,
+                INDENT.repeat(indent),
+                closing
+            )
+        }
+    }
+
+    fn has_comments_outside_blocks(&self, list: &Ast) -> bool {
+        fn blocks(ast: &Ast, spans: &mut Vec<(usize, usize)>) {
+            match &ast.node {
+                AstNode::Block(_) | AstNode::Module(_) => {
+                    spans.push((ast.location.start, ast.location.end))
+                }
+                AstNode::List(items) => {
+                    for item in items {
+                        blocks(item, spans);
+                    }
+                }
+                AstNode::Quoted(value) => blocks(value, spans),
+                _ => {}
+            }
+        }
+
+        let mut block_spans = Vec::new();
+        blocks(list, &mut block_spans);
+
+        self.comments.iter().any(|comment| {
+            (list.location.start..list.location.end).contains(&comment.start)
+                && !block_spans
+                    .iter()
+                    .any(|&(start, end)| (start..end).contains(&comment.start))
+        })
+    }
+}
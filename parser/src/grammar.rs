@@ -1,3 +1,7 @@
+// Rule parameters are passed alongside the parser's own arguments
+#![allow(clippy::too_many_arguments)]
+
+use crate::Trivia;
 use bigdecimal::BigDecimal;
 use line_col::LineColLookup;
 use peg::parser;
@@ -7,6 +11,10 @@ use snailquote::unescape;
 pub struct Ast {
     pub node: AstNode,
     pub location: SourceLocation,
+
+    /// The whitespace and comments around the node, recorded when parsing in
+    /// lossless mode
+    pub trivia: Option<Trivia>,
 }
 
 #[derive(Clone)]
@@ -18,93 +26,165 @@ pub enum AstNode {
     Text(String),
     Number(BigDecimal),
     Quoted(Box<Ast>),
+    Error {
+        message: String,
+        expected: Vec<String>,
+    },
 }
 
 #[derive(Clone)]
 pub struct AstNodeStatement {
     pub items: Vec<Ast>,
     pub location: SourceLocation,
+    pub trivia: Option<Trivia>,
+
+    /// The text of the '---' comments on the lines directly above the
+    /// statement, without the leading '---'
+    pub documentation: Option<String>,
 }
 
 #[derive(Clone)]
 pub struct SourceLocation {
+    /// The line and column of the start of the span, in bytes
     pub line: usize,
     pub column: usize,
+
+    /// The byte offsets of the start and end (exclusive) of the span
+    pub start: usize,
+    pub end: usize,
 }
 
-fn source_location(lc: &LineColLookup, offset: usize) -> SourceLocation {
-    let (line, column) = lc.get(offset);
+fn source_location(lc: &LineColLookup, start: usize, end: usize) -> SourceLocation {
+    let (line, column) = lc.get(start);
 
-    SourceLocation { line, column }
+    SourceLocation {
+        line,
+        column,
+        start,
+        end,
+    }
 }
 
-fn ast(node: AstNode, lc: &LineColLookup, offset: usize) -> Ast {
+fn ast(node: AstNode, lc: &LineColLookup, start: usize, end: usize) -> Ast {
     Ast {
         node,
-        location: source_location(lc, offset),
+        location: source_location(lc, start, end),
+        trivia: None,
     }
 }
 
+fn error_node(
+    message: &str,
+    expected: &[&str],
+    lc: &LineColLookup,
+    start: usize,
+    end: usize,
+) -> Ast {
+    ast(
+        AstNode::Error {
+            message: String::from(message),
+            expected: expected.iter().map(|&token| String::from(token)).collect(),
+        },
+        lc,
+        start,
+        end,
+    )
+}
+
+const NEW_LINE_OR_VALUE: &[&str] = &["a new line", "a value"];
+
+/// Describe the bracket at the current position for error messages.
+fn found(bracket: &str) -> String {
+    if bracket.is_empty() {
+        String::from("the end of the file")
+    } else {
+        format!("`{}`", bracket)
+    }
+}
+
+fn with_error_statement(
+    mut statements: Vec<AstNodeStatement>,
+    error: Option<Ast>,
+) -> Vec<AstNodeStatement> {
+    if let Some(error) = error {
+        statements.push(AstNodeStatement {
+            location: error.location.clone(),
+            items: vec![error],
+            trivia: None,
+            documentation: None,
+        });
+    }
+
+    statements
+}
+
 parser! {
-    pub grammar grammar(lc: &LineColLookup) for str {
+    pub grammar grammar(lc: &LineColLookup, recover: bool) for str {
         use AstNode::*;
 
         pub rule inline_program() -> Ast
-            = p:position!() ws() statements:statements() ws() eof()
-            { ast(Block(statements), lc, p) }
+            = p:position!() ws() statements:statements("") ws() e:position!() eof()
+            { ast(Block(statements), lc, p, e) }
 
         pub rule file() -> Ast
-            = p:position!() ws() statements:statements() ws() eof()
-            { ast(Module(statements), lc, p) }
+            = p:position!() ws() statements:statements("") ws() e:position!() eof()
+            { ast(Module(statements), lc, p, e) }
 
-        rule value() -> Ast
-            = block() / module() / list() / quoted() / text() / number() / name()
+        rule value(closers: &str) -> Ast
+            = block(closers) / module(closers) / list(closers) / quoted(closers) / text() / number() / name()
 
         rule name() -> Ast
-            = p:position!() name:$(!(" " / "\t" / "\r" / "\n" / "(" / ")" / "[" / "]" / "{" / "}" / "'" / "\"") [_])+
+            = p:position!() !"--" name:$(!(" " / "\t" / "\r" / "\n" / "(" / ")" / "[" / "]" / "{" / "}" / "'" / "\"") [_])+ e:position!()
             {
-                ast(Name(name.join("")), lc, p)
+                ast(Name(name.join("")), lc, p, e)
             }
 
         rule number() -> Ast
-            = p:position!() number:$("-"? ['0'..='9']+ ("." ['0'..='9']+)?)
-            { ast(Number(number.parse().unwrap()), lc, p) }
+            = p:position!() number:$("-"? ['0'..='9']+ ("." ['0'..='9']+)?) e:position!()
+            { ast(Number(number.parse().unwrap()), lc, p, e) }
 
         rule text() -> Ast
-            = p:position!() "\"" text:$((!"\"" [_])*) "\""
+            = p:position!() "\"" text:$((!"\"" [_])*) "\"" e:position!()
             {?
                 match unescape(text) {
-                    Ok(text) => Ok(ast(Text(text), lc, p)),
+                    Ok(text) => Ok(ast(Text(text), lc, p, e)),
                     Err(_) => Err("Invalid escape character")
                 }
             }
 
-        rule quoted() -> Ast
-            = p:position!() "'" value:value()
-            { ast(Quoted(Box::new(value)), lc, p) }
+        rule quoted(closers: &str) -> Ast
+            = p:position!() "'" value:value(closers) e:position!()
+            { ast(Quoted(Box::new(value)), lc, p, e) }
 
-        rule list() -> Ast
-            = p:position!() "(" ws() items:list_item()* ws() ")"
-            { ast(List(items), lc, p) }
+        rule list(closers: &str) -> Ast
+            = p:position!() "(" ws() items:list_item((&format!("{})", closers)))* ws()
+              error:(")" { None } / e:unclosed("(", ")", closers, p) { Some(e) }) e:position!()
+            {
+                let mut items = items;
+                items.extend(error);
+                ast(List(items), lc, p, e)
+            }
 
-        rule list_item() -> Ast
-            = ws() value:value()
-            { value }
+        rule list_item(closers: &str) -> Ast
+            = ws() value:value(closers) { value }
+            / ws() error:error_item(closers) { error }
 
-        rule block() -> Ast
-            = p:position!() "[" ws() statements:statements() ws() "]"
-            { ast(Block(statements), lc, p) }
+        rule block(closers: &str) -> Ast
+            = p:position!() "[" ws() statements:statements((&format!("{}]", closers))) ws()
+              error:("]" { None } / e:unclosed("[", "]", closers, p) { Some(e) }) e:position!()
+            { ast(Block(with_error_statement(statements, error)), lc, p, e) }
 
-        rule module() -> Ast
-            = p:position!() "{" ws() statements:statements() ws() "}"
-            { ast(Module(statements), lc, p) }
+        rule module(closers: &str) -> Ast
+            = p:position!() "{" ws() statements:statements((&format!("{}}}", closers))) ws()
+              error:("}" { None } / e:unclosed("{", "}", closers, p) { Some(e) }) e:position!()
+            { ast(Module(with_error_statement(statements, error)), lc, p, e) }
 
-        rule statements() -> Vec<AstNodeStatement>
-            = statements:statement()*
+        rule statements(closers: &str) -> Vec<AstNodeStatement>
+            = statements:statement(closers)*
             { statements.iter().filter(|s| !s.items.is_empty()).cloned().collect() }
 
-        rule statement() -> AstNodeStatement
-            = p:position!() first:value()? rest:statement_item()* sp()* comment()? nl()+
+        rule statement(closers: &str) -> AstNodeStatement
+            = sp()* p:position!() first:value(closers)? rest:statement_item(closers)* e:position!() sp()* comment()? error:statement_end(closers, (p == e))
             {
                 let mut items = rest;
 
@@ -112,15 +192,94 @@ parser! {
                     items.insert(0, first);
                 }
 
+                items.extend(error);
+
                 AstNodeStatement {
                     items,
-                    location: source_location(lc, p)
+                    location: source_location(lc, p, e),
+                    trivia: None,
+                    documentation: None,
+                }
+            }
+
+        rule statement_item(closers: &str) -> Ast
+            = sp()* value:value(closers) { value }
+            / sp()* error:error_item(closers) { error }
+
+        rule statement_end(closers: &str, is_empty: bool) -> Option<Ast>
+            = nl()+ { None }
+            / quiet! {
+                recovering() p:position!() next:next_bracket()
+                {?
+                    if is_empty {
+                        // Statements must contain something
+                        Err("statement")
+                    } else if !next.is_empty() && closers.ends_with(next) {
+                        // Statements at the end of a bracket must still be
+                        // followed by a new line; other brackets are reported
+                        // as unclosed by the bracket they're inside
+                        Ok(Some(error_node(&format!("expected a new line or a value, found {}", found(next)), NEW_LINE_OR_VALUE, lc, p, p)))
+                    } else {
+                        Ok(None)
+                    }
                 }
             }
 
-        rule statement_item() -> Ast
-            = sp()* value:value()
-            { value }
+        /// Report that a bracket isn't closed. Closing brackets that don't
+        /// belong to a surrounding list, block or module are assumed to be a
+        /// typo and consumed; otherwise they're left for the surrounding code
+        /// to close.
+        rule unclosed(opening: &str, closing: &str, closers: &str, start: usize) -> Ast
+            = quiet! {
+                recovering() p:position!() next:next_bracket() (
+                    bracket:$(")" / "]" / "}") {? if closers.contains(bracket) { Err("closing bracket") } else { Ok(()) } }
+                )? e:position!()
+                {
+                    let (line, column) = lc.get(start);
+
+                    error_node(&format!(
+                        "unclosed `{}` opened at {}:{}; expected `{}`, found {}",
+                        opening, line, column, closing, found(next)
+                    ), &[&format!("`{}`", closing), "a new line", "a value"], lc, p, e)
+                }
+            }
+
+        /// Consume code that can't be parsed as a value, so parsing can
+        /// continue after it.
+        rule error_item(closers: &str) -> Ast
+            = quiet! {
+                recovering()
+                error:(
+                    p:position!() bracket:$(")" / "]" / "}") e:position!()
+                    {?
+                        // Inside a list, block or module, any closing bracket
+                        // ends the current statement instead
+                        if closers.is_empty() {
+                            Ok(error_node(&format!("unexpected `{}` without a matching opening bracket", bracket), NEW_LINE_OR_VALUE, lc, p, e))
+                        } else {
+                            Err("closing bracket")
+                        }
+                    }
+                    / p:position!() "\"" (!"\"" [_])* "\"" e:position!()
+                    { error_node("invalid escape sequence in text", &[], lc, p, e) }
+                    / start:position!() "\"" (!nl() [_])* p:position!()
+                    {
+                        let (line, column) = lc.get(start);
+                        error_node(&format!("unterminated text starting at {}:{}", line, column), &["a closing `\"`"], lc, p, p)
+                    }
+                    / p:position!() "'" e:position!()
+                    { error_node("expected a value after `'`", &["a value"], lc, p, e) }
+                )
+                { error }
+            }
+
+        /// Look at the closing bracket at the current position without
+        /// consuming it, or an empty string at the end of the file.
+        rule next_bracket() -> &'static str
+            = &")" { ")" } / &"]" { "]" } / &"}" { "}" } / eof() { "" }
+
+        rule recovering()
+            = {? if recover { Ok(()) } else { Err("recovering") } }
 
         rule comment()
             = "--" (!nl() [_])*
@@ -1,9 +1,18 @@
 #[cfg(feature = "convert")]
 mod convert;
+mod diagnostics;
+mod documentation;
+mod format;
 mod grammar;
 mod parser;
+mod scan;
+mod trivia;
 
 #[cfg(feature = "convert")]
 pub use convert::*;
+pub use diagnostics::*;
+pub use format::*;
 pub use grammar::*;
 pub use parser::*;
+pub use scan::*;
+pub use trivia::*;
@@ -1,31 +1,95 @@
-use crate::grammar::*;
+use crate::{documentation::attach_documentation, grammar::*, trivia::attach_trivia};
 use line_col::LineColLookup;
 
 pub type Result = std::result::Result<Ast, Error>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Error {
     pub message: String,
     pub line: usize,
     pub column: usize,
+    pub offset: usize,
+    pub expected: Vec<String>,
 }
 
 macro_rules! parse_rule {
-    ($code:expr, $rule:ident) => {{
+    ($code:expr, $rule:ident, $recover:expr) => {{
         let code = String::from($code) + "\n";
 
-        grammar::$rule(&code, &LineColLookup::new(&code)).map_err(|error| Error {
-            message: error.to_string(),
-            line: error.location.line,
-            column: error.location.column,
-        })
+        grammar::$rule(&code, &LineColLookup::new(&code), $recover)
+            .map(|mut ast| {
+                // Don't include the newline added above in the span
+                ast.location.end = ast.location.end.min($code.len());
+                attach_documentation(&mut ast, $code);
+                ast
+            })
+            .map_err(|error| {
+                Error::from_parse_error($code, error.location.offset, error.expected.tokens())
+            })
+    }};
+}
+
+macro_rules! parse_rule_recovering {
+    ($code:expr, $rule:ident) => {{
+        match parse_rule!($code, $rule, true) {
+            Ok(ast) => {
+                let errors = Error::collect_from_ast($code, &ast);
+                (ast, errors)
+            }
+            // Recovery should always produce an AST, but fall back to
+            // reporting the single error just in case
+            Err(error) => (
+                Ast {
+                    node: AstNode::Error {
+                        message: error.message.clone(),
+                        expected: error.expected.clone(),
+                    },
+                    location: SourceLocation {
+                        line: error.line,
+                        column: error.column,
+                        start: error.offset,
+                        end: error.offset,
+                    },
+                    trivia: None,
+                },
+                vec![error],
+            ),
+        }
     }};
 }
 
 pub fn parse_file(code: &str) -> Result {
-    parse_rule!(code, file)
+    parse_rule!(code, file, false)
 }
 
 pub fn parse_inline_program(code: &str) -> Result {
-    parse_rule!(code, inline_program)
+    parse_rule!(code, inline_program, false)
+}
+
+/// Parse a file, continuing past syntax errors. The returned AST contains an
+/// error node wherever the code couldn't be parsed, and every error is
+/// returned in the order it appears in the code.
+pub fn parse_file_recovering(code: &str) -> (Ast, Vec<Error>) {
+    parse_rule_recovering!(code, file)
+}
+
+/// Parse an inline program, continuing past syntax errors (see
+/// 'parse_file_recovering').
+pub fn parse_inline_program_recovering(code: &str) -> (Ast, Vec<Error>) {
+    parse_rule_recovering!(code, inline_program)
+}
+
+/// Parse a file, recording the whitespace and comments around each node so
+/// the code can be regenerated exactly using 'print_lossless'.
+pub fn parse_file_lossless(code: &str) -> Result {
+    let mut ast = parse_file(code)?;
+    attach_trivia(&mut ast, code);
+    Ok(ast)
+}
+
+/// Parse an inline program in lossless mode (see 'parse_file_lossless').
+pub fn parse_inline_program_lossless(code: &str) -> Result {
+    let mut ast = parse_inline_program(code)?;
+    attach_trivia(&mut ast, code);
+    Ok(ast)
 }
@@ -0,0 +1,107 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bracket {
+    pub bracket: char,
+    pub offset: usize,
+}
+
+impl Bracket {
+    pub fn closing(&self) -> char {
+        match self.bracket {
+            '(' => ')',
+            '[' => ']',
+            '{' => '}',
+            _ => unreachable!("Not an opening bracket"),
+        }
+    }
+}
+
+/// A '--' comment, which continues until the end of the line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    /// The text of the comment, including the leading '--'
+    pub text: String,
+
+    /// The byte offsets of the start and end (exclusive) of the comment
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Default)]
+pub(crate) struct Scan {
+    pub unclosed: Vec<Bracket>,
+    pub unmatched: Option<Bracket>,
+    pub unterminated_text: Option<usize>,
+    pub comments: Vec<Comment>,
+}
+
+/// Walk through the code, ignoring brackets inside text and comments.
+pub(crate) fn scan(code: &str) -> Scan {
+    let mut scan = Scan::default();
+    let mut comment_start = None;
+    let mut previous = '\n';
+    let mut chars = code.char_indices().peekable();
+
+    let end_comment = |scan: &mut Scan, start: usize, end: usize| {
+        let text = code[start..end].trim_end();
+
+        scan.comments.push(Comment {
+            text: String::from(text),
+            start,
+            end: start + text.len(),
+        });
+    };
+
+    while let Some((offset, c)) = chars.next() {
+        if scan.unterminated_text.is_some() {
+            if c == '"' {
+                scan.unterminated_text = None;
+            }
+        } else if let Some(start) = comment_start {
+            if c == '\n' {
+                end_comment(&mut scan, start, offset);
+                comment_start = None;
+            }
+        } else {
+            match c {
+                '"' => scan.unterminated_text = Some(offset),
+                // '--' inside a name (eg. 'a--b') doesn't start a comment
+                '-' if (previous.is_whitespace() || "()[]{}\"".contains(previous))
+                    && matches!(chars.peek(), Some((_, '-'))) =>
+                {
+                    comment_start = Some(offset)
+                }
+                '(' | '[' | '{' => scan.unclosed.push(Bracket { bracket: c, offset }),
+                ')' | ']' | '}' => match scan.unclosed.last() {
+                    Some(bracket) if bracket.closing() == c => {
+                        scan.unclosed.pop();
+                    }
+                    _ => {
+                        if scan.unmatched.is_none() {
+                            scan.unmatched = Some(Bracket { bracket: c, offset });
+                        }
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        previous = c;
+    }
+
+    if let Some(start) = comment_start {
+        end_comment(&mut scan, start, code.len());
+    }
+
+    scan
+}
+
+/// Find the brackets that are opened but never closed in the code, ignoring
+/// brackets inside text and comments.
+pub fn unclosed_brackets(code: &str) -> Vec<Bracket> {
+    scan(code).unclosed
+}
+
+/// Find the comments in the code, which the parser otherwise discards.
+pub fn comments(code: &str) -> Vec<Comment> {
+    scan(code).comments
+}
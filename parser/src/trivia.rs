@@ -0,0 +1,216 @@
+use crate::*;
+
+/// The text around a node that doesn't affect its meaning. Together with the
+/// nodes themselves, trivia records every character of the code, so the code
+/// can be regenerated exactly using 'print_lossless'.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trivia {
+    /// The whitespace and comments before the node. For statements, this
+    /// includes the newline ending the previous statement and any blank lines
+    /// or comments on their own line.
+    pub leading: String,
+
+    /// For statements, the whitespace and comment after the last item on the
+    /// same line. For lists, blocks, modules and files, the whitespace and
+    /// comments after the last item or statement, before the closing bracket
+    /// or the end of the file.
+    pub trailing: String,
+
+    /// The text or number as it was written, eg. with escape sequences or
+    /// trailing zeros. Set this to 'None' after changing the value so the new
+    /// value is printed instead.
+    pub token: Option<String>,
+}
+
+/// Record the trivia of every node in an AST parsed from the code.
+pub(crate) fn attach_trivia(ast: &mut Ast, code: &str) {
+    let end = match &mut ast.node {
+        AstNode::Block(statements) | AstNode::Module(statements) => {
+            attach_to_statements(statements, code, 0)
+        }
+        _ => {
+            attach_to_node(ast, code);
+            ast.location.end
+        }
+    };
+
+    ast.trivia = Some(Trivia {
+        trailing: String::from(&code[end..]),
+        ..Default::default()
+    });
+}
+
+/// Returns the offset after the last statement's trailing trivia.
+fn attach_to_statements(statements: &mut [AstNodeStatement], code: &str, start: usize) -> usize {
+    let mut offset = start;
+
+    for statement in statements {
+        let line_end = code[statement.location.end..]
+            .find('\n')
+            .map(|index| statement.location.end + index)
+            .unwrap_or_else(|| code.len());
+
+        statement.trivia = Some(Trivia {
+            leading: String::from(&code[offset..statement.location.start]),
+            trailing: String::from(&code[statement.location.end..line_end]),
+            token: None,
+        });
+
+        attach_to_items(&mut statement.items, code, statement.location.start);
+
+        offset = line_end;
+    }
+
+    offset
+}
+
+/// Returns the offset after the last item.
+fn attach_to_items(items: &mut [Ast], code: &str, start: usize) -> usize {
+    let mut offset = start;
+
+    for item in items {
+        let leading = String::from(&code[offset..item.location.start]);
+        attach_to_node(item, code);
+
+        if let Some(trivia) = &mut item.trivia {
+            trivia.leading = leading;
+        }
+
+        offset = item.location.end;
+    }
+
+    offset
+}
+
+fn attach_to_node(ast: &mut Ast, code: &str) {
+    let mut trivia = Trivia::default();
+
+    // The brackets are one byte each
+    let inside = ast.location.start + 1;
+    let closing = ast.location.end.saturating_sub(1);
+
+    match &mut ast.node {
+        AstNode::Block(statements) | AstNode::Module(statements) => {
+            let end = attach_to_statements(statements, code, inside);
+            trivia.trailing = String::from(&code[end..closing]);
+        }
+        AstNode::List(items) => {
+            let end = attach_to_items(items, code, inside);
+            trivia.trailing = String::from(&code[end..closing]);
+        }
+        AstNode::Quoted(value) => attach_to_node(value, code),
+        AstNode::Text(_) | AstNode::Number(_) => {
+            trivia.token = Some(String::from(&code[ast.location.start..ast.location.end]));
+        }
+        AstNode::Name(_) | AstNode::Error { .. } => {}
+    }
+
+    ast.trivia = Some(trivia);
+}
+
+/// Regenerate the code of a file or inline program from its AST. ASTs parsed
+/// in lossless mode are printed exactly as they were written; nodes without
+/// trivia are separated by single spaces and newlines.
+pub fn print_lossless(ast: &Ast) -> String {
+    let mut output = String::new();
+
+    match &ast.node {
+        AstNode::Block(statements) | AstNode::Module(statements) => {
+            print_statements(statements, &mut output)
+        }
+        _ => print_node(ast, &mut output),
+    }
+
+    match &ast.trivia {
+        Some(trivia) => output.push_str(&trivia.trailing),
+        None => output.push('\n'),
+    }
+
+    output
+}
+
+fn print_statements(statements: &[AstNodeStatement], output: &mut String) {
+    for (index, statement) in statements.iter().enumerate() {
+        match &statement.trivia {
+            Some(trivia) => output.push_str(&trivia.leading),
+            None if index > 0 => output.push('\n'),
+            None => {}
+        }
+
+        print_items(&statement.items, output);
+
+        if let Some(trivia) = &statement.trivia {
+            output.push_str(&trivia.trailing);
+        }
+    }
+}
+
+fn print_items(items: &[Ast], output: &mut String) {
+    for (index, item) in items.iter().enumerate() {
+        match &item.trivia {
+            Some(trivia) => output.push_str(&trivia.leading),
+            None if index > 0 => output.push(' '),
+            None => {}
+        }
+
+        print_node(item, output);
+    }
+}
+
+fn print_node(ast: &Ast, output: &mut String) {
+    let trivia = ast.trivia.as_ref();
+
+    let token = trivia.and_then(|trivia| trivia.token.as_ref());
+
+    match &ast.node {
+        AstNode::Name(name) => output.push_str(name),
+        AstNode::Text(text) => match token {
+            Some(token) => output.push_str(token),
+            None => output.push_str(&format!("{:?}", text)),
+        },
+        AstNode::Number(number) => match token {
+            Some(token) => output.push_str(token),
+            None => output.push_str(&number.to_string()),
+        },
+        AstNode::Quoted(value) => {
+            output.push('\'');
+            print_node(value, output);
+        }
+        AstNode::List(items) => {
+            output.push('(');
+            print_items(items, output);
+
+            if let Some(trivia) = trivia {
+                output.push_str(&trivia.trailing);
+            }
+
+            output.push(')');
+        }
+        AstNode::Block(statements) => {
+            output.push('[');
+            print_block(statements, trivia, output);
+            output.push(']');
+        }
+        AstNode::Module(statements) => {
+            output.push('{');
+            print_block(statements, trivia, output);
+            output.push('}');
+        }
+        AstNode::Error { .. } => {}
+    }
+}
+
+fn print_block(statements: &[AstNodeStatement], trivia: Option<&Trivia>, output: &mut String) {
+    // Statements must start on a new line
+    if trivia.is_none() && !statements.is_empty() {
+        output.push('\n');
+    }
+
+    print_statements(statements, output);
+
+    match trivia {
+        Some(trivia) => output.push_str(&trivia.trailing),
+        None if !statements.is_empty() => output.push('\n'),
+        None => {}
+    }
+}
@@ -0,0 +1,57 @@
+use wipple_parser::*;
+
+fn format(code: &str) -> String {
+    format_file(code).unwrap_or_else(|error| panic!("{}", error.render(code, None)))
+}
+
+#[test]
+fn normalises_whitespace_and_indentation() {
+    let code = "x   :  1\n\n\n\nf : x ->    [\n  show  x\n      x\n]\n";
+
+    assert_eq!(format(code), "x : 1\n\nf : x -> [\n    show x\n    x\n]\n");
+}
+
+#[test]
+fn keeps_comments() {
+    let code = "-- The answer\nx : 42   -- not 43\n\nf : x -> [\n-- Inside a block\n  x\n]\n";
+
+    assert_eq!(
+        format(code),
+        "-- The answer\nx : 42 -- not 43\n\nf : x -> [\n    -- Inside a block\n    x\n]\n"
+    );
+}
+
+#[test]
+fn keeps_text_and_numbers_as_written() {
+    let code = "x : \"a\\tb\"\ny : 1.50\n";
+
+    assert_eq!(format(code), code);
+}
+
+#[test]
+fn formats_lists_modules_and_quotes() {
+    let code = "m : {  a : ( 1   2 )\nb : '(x  y)\n}\n";
+
+    assert_eq!(format(code), "m : {\n    a : (1 2)\n    b : '(x y)\n}\n");
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    let examples = [
+        "x   :  1\n\n\n\nf : x ->    [\n  show  x\n      x\n]\n",
+        "-- The answer\nx : 42   -- not 43\n\nf : x -> [\n-- Inside a block\n  x\n]\n",
+        "m : {  a : ( 1   2 )\nb : '(x  y)\n}\n",
+        "result : loop [\n    if (x = 3) (break (x * 10)) _ -- stop\n]\n",
+        "--- Documentation\ng : f\n\n\n-- Trailing comment\n",
+    ];
+
+    for code in examples {
+        let formatted = format(code);
+        assert_eq!(format(&formatted), formatted, "formatting {:?}", code);
+    }
+}
+
+#[test]
+fn syntax_errors_are_reported() {
+    assert!(format_file("x : (1 2").is_err());
+}
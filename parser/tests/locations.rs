@@ -0,0 +1,139 @@
+use wipple_parser::*;
+
+/// Describe each node in the AST along with the byte offsets of its start and
+/// end, outermost first.
+fn spans(code: &str) -> Vec<(String, usize, usize)> {
+    fn collect(ast: &Ast, spans: &mut Vec<(String, usize, usize)>) {
+        let description = match &ast.node {
+            AstNode::Block(_) => String::from("block"),
+            AstNode::Module(_) => String::from("module"),
+            AstNode::List(_) => String::from("list"),
+            AstNode::Name(name) => name.clone(),
+            AstNode::Text(text) => format!("{:?}", text),
+            AstNode::Number(number) => number.to_string(),
+            AstNode::Quoted(_) => String::from("quoted"),
+            AstNode::Error { message, .. } => format!("error: {}", message),
+        };
+
+        spans.push((description, ast.location.start, ast.location.end));
+
+        match &ast.node {
+            AstNode::Block(statements) | AstNode::Module(statements) => {
+                for statement in statements {
+                    spans.push((
+                        String::from("statement"),
+                        statement.location.start,
+                        statement.location.end,
+                    ));
+
+                    for item in &statement.items {
+                        collect(item, spans);
+                    }
+                }
+            }
+            AstNode::List(items) => {
+                for item in items {
+                    collect(item, spans);
+                }
+            }
+            AstNode::Quoted(value) => collect(value, spans),
+            _ => {}
+        }
+    }
+
+    let ast = parse_file(code).unwrap_or_else(|error| panic!("{}", error.render(code, None)));
+
+    let mut spans = Vec::new();
+    collect(&ast, &mut spans);
+    spans
+}
+
+fn span(description: &str, start: usize, end: usize) -> (String, usize, usize) {
+    (String::from(description), start, end)
+}
+
+#[test]
+fn nested_lists() {
+    assert_eq!(
+        spans("f (a (b c)) d\n"),
+        vec![
+            span("module", 0, 14),
+            span("statement", 0, 13),
+            span("f", 0, 1),
+            span("list", 2, 11),
+            span("a", 3, 4),
+            span("list", 5, 10),
+            span("b", 6, 7),
+            span("c", 8, 9),
+            span("d", 12, 13),
+        ]
+    );
+}
+
+#[test]
+fn blocks() {
+    // Statements start after their indentation
+    assert_eq!(
+        spans("x : [\n    y : 1\n    z\n]\n"),
+        vec![
+            span("module", 0, 24),
+            span("statement", 0, 23),
+            span("x", 0, 1),
+            span(":", 2, 3),
+            span("block", 4, 23),
+            span("statement", 10, 15),
+            span("y", 10, 11),
+            span(":", 12, 13),
+            span("1", 14, 15),
+            span("statement", 20, 21),
+            span("z", 20, 21),
+        ]
+    );
+}
+
+#[test]
+fn modules_and_quoted_values() {
+    assert_eq!(
+        spans("m : {\n    a : 'b\n}\nq : '(a 'b)\n"),
+        vec![
+            span("module", 0, 31),
+            span("statement", 0, 18),
+            span("m", 0, 1),
+            span(":", 2, 3),
+            span("module", 4, 18),
+            span("statement", 10, 16),
+            span("a", 10, 11),
+            span(":", 12, 13),
+            span("quoted", 14, 16),
+            span("b", 15, 16),
+            span("statement", 19, 30),
+            span("q", 19, 20),
+            span(":", 21, 22),
+            span("quoted", 23, 30),
+            span("list", 24, 30),
+            span("a", 25, 26),
+            span("quoted", 27, 29),
+            span("b", 28, 29),
+        ]
+    );
+}
+
+#[test]
+fn multi_byte_text() {
+    // Offsets count bytes, not characters
+    assert_eq!(
+        spans("t : \"héllo\" é\nu : \"日本\"\n"),
+        vec![
+            span("module", 0, 29),
+            span("statement", 0, 15),
+            span("t", 0, 1),
+            span(":", 2, 3),
+            span("\"héllo\"", 4, 12),
+            span("é", 13, 15),
+            span("statement", 16, 28),
+            span("u", 16, 17),
+            span(":", 18, 19),
+            span("\"日本\"", 20, 28),
+        ]
+    );
+}
@@ -0,0 +1,51 @@
+use wipple_parser::*;
+
+fn assert_round_trip(code: &str) {
+    let ast =
+        parse_file_lossless(code).unwrap_or_else(|error| panic!("{}", error.render(code, None)));
+    assert_eq!(print_lossless(&ast), code);
+
+    let ast = parse_inline_program_lossless(code)
+        .unwrap_or_else(|error| panic!("{}", error.render(code, None)));
+    assert_eq!(print_lossless(&ast), code);
+}
+
+#[test]
+fn comments() {
+    assert_round_trip(
+        "-- A comment\nx : 1 -- after a statement\n--- Documentation\nf : x -> x\n-- At the end",
+    );
+}
+
+#[test]
+fn blank_lines() {
+    assert_round_trip("\n\nx : 1\n\n\n\ny : 2\n\n");
+}
+
+#[test]
+fn odd_spacing() {
+    assert_round_trip("  x   :\t1  \ny :(  1   2\t)\n\tz : '(a  b )\n");
+}
+
+#[test]
+fn nested_brackets() {
+    assert_round_trip(
+        "f : x -> [\n  -- Inside a block\n\n    y : { a : ( 1\n  2 )\n   }   \n\n  y  -- result\n    ]\n",
+    );
+}
+
+#[test]
+fn text_and_numbers_as_written() {
+    assert_round_trip("x : \"a\\tb\"\ny : 1.50\nz : -007\n");
+}
+
+#[test]
+fn windows_line_endings() {
+    assert_round_trip("x : 1\r\n\r\ny : [\r\n    x\r\n]\r\n");
+}
+
+#[test]
+fn empty() {
+    assert_round_trip("");
+    assert_round_trip("   \n\n");
+}
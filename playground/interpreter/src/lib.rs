@@ -3,7 +3,7 @@ use std::cell::RefCell;
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wipple::*;
-use wipple_parser::{convert, parse_inline_program};
+use wipple_parser::{convert, parse_inline_program_recovering, render_errors};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ShownValue {
@@ -16,8 +16,21 @@ pub struct InterpreterResult {
     pub success: bool,
     pub output: Option<Vec<ShownValue>>,
     pub error: Option<String>,
+
+    /// Whether the program was stopped because it took too long
+    pub timed_out: bool,
 }
 
+/// The size of the stack of WebAssembly modules built by Rust.
+const STACK_SIZE: usize = 1024 * 1024;
+
+/// The number of evaluations and calls a program can make before it's stopped.
+const MAX_STEPS: usize = 250_000;
+
+/// The approximate number of bytes a program can hold at once before it's
+/// stopped.
+const MAX_ALLOCATED: usize = 16 * 1024 * 1024;
+
 #[wasm_bindgen]
 pub fn run(code: &str) -> JsValue {
     #[cfg(feature = "console_error_panic_hook")]
@@ -29,29 +42,16 @@ pub fn run(code: &str) -> JsValue {
 }
 
 fn run_code(code: &str) -> InterpreterResult {
-    let ast = match parse_inline_program(code) {
-        Ok(value) => value,
-        Err(error) => {
-            return InterpreterResult {
-                success: false,
-                output: None,
-                error: Some(
-                    Error::new(
-                        &error.message,
-                        &Stack::new().add_location(
-                            || String::from("Parsing input"),
-                            &SourceLocation {
-                                file: None,
-                                line: error.line,
-                                column: error.column,
-                            },
-                        ),
-                    )
-                    .to_string(),
-                ),
-            }
-        }
-    };
+    let (ast, errors) = parse_inline_program_recovering(code);
+
+    if !errors.is_empty() {
+        return InterpreterResult {
+            success: false,
+            output: None,
+            error: Some(render_errors(&errors, code, None)),
+            timed_out: false,
+        };
+    }
 
     let value = convert(&ast, None);
 
@@ -61,17 +61,25 @@ fn run_code(code: &str) -> InterpreterResult {
     setup_playground(&output);
 
     let env = Environment::child_of(&Environment::global()).into_ref();
-    let stack = Stack::new();
+    let mut stack = Stack::new();
+    stack.max_depth = max_depth_for_stack_size(STACK_SIZE);
+
+    // Programs that never finish or keep growing would freeze the page
+    stack.budget = Budget::new()
+        .with_max_steps(MAX_STEPS)
+        .with_max_allocated(MAX_ALLOCATED);
 
     match value.evaluate(&env, &stack) {
         Ok(_) => InterpreterResult {
             success: true,
             output: Some(output.as_ref().clone().get_mut().clone()),
             error: None,
+            timed_out: false,
         },
         Err(state) => InterpreterResult {
             success: false,
             output: None,
+            timed_out: matches!(state, ReturnState::OutOfTime(_)),
             error: Some(state.into_error(&stack).to_string()),
         },
     }
@@ -0,0 +1,47 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+use wipple::*;
+
+thread_local! {
+    static MODULE_CACHE: RefCell<HashMap<PathBuf, Module>> = Default::default();
+}
+
+/// Return the module previously imported from this path, or import it and
+/// remember the result so the file is only evaluated once.
+pub(crate) fn cached_module(
+    path: &Path,
+    import: impl FnOnce() -> Result<Module>,
+) -> Result<Module> {
+    // Files that can't be found aren't cached so the error is reported by the
+    // import itself
+    let key = match path.canonicalize() {
+        Ok(path) => path,
+        Err(_) => return import(),
+    };
+
+    if let Some(module) = MODULE_CACHE.with(|cache| cache.borrow().get(&key).cloned()) {
+        return Ok(module);
+    }
+
+    let module = import()?;
+
+    MODULE_CACHE.with(|cache| cache.borrow_mut().insert(key, module.clone()));
+
+    Ok(module)
+}
+
+/// Forget the module imported from this path, so the next import evaluates the
+/// file again.
+pub fn invalidate_module(path: &Path) {
+    let key = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+    MODULE_CACHE.with(|cache| cache.borrow_mut().remove(&key));
+}
+
+/// Forget every imported module.
+pub fn clear_module_cache() {
+    MODULE_CACHE.with(|cache| cache.borrow_mut().clear());
+}
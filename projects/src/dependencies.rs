@@ -0,0 +1,457 @@
+use crate::*;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs, io,
+    path::{Component, Path, PathBuf},
+    process::Command,
+};
+use wipple::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencySource {
+    /// A folder on disk, relative to the project root
+    Path(PathBuf),
+
+    /// A Git repository
+    Git(String),
+}
+
+#[derive(Clone)]
+pub struct Dependency {
+    pub source: DependencySource,
+}
+
+impl Primitive for Dependency {}
+
+/// The folder dependencies are installed into, relative to the project root.
+pub const DEPENDENCIES_FOLDER: &str = ".wipple/dependencies";
+
+type InstalledDependencies = HashMap<PathBuf, HashMap<String, PathBuf>>;
+
+thread_local! {
+    static INSTALLED_DEPENDENCIES: RefCell<InstalledDependencies> = Default::default();
+}
+
+/// Add the functions used to declare dependencies to the environment of a
+/// project file.
+pub fn setup_dependencies(env: &mut Environment) {
+    env.add_text_conformance(TraitID::new_primitive::<Dependency>(), "dependency");
+
+    env.set_variable(
+        "path",
+        Value::of(Function::new(|value, env, stack| {
+            let path = value.evaluate(env, stack)?.get_primitive_or::<Text>(
+                "Expected a path to a folder",
+                env,
+                stack,
+            )?;
+
+            Ok(Value::of(Dependency {
+                source: DependencySource::Path(PathBuf::from(path.text)),
+            }))
+        })),
+    );
+
+    env.set_variable(
+        "git",
+        Value::of(Function::new(|value, env, stack| {
+            let url = value.evaluate(env, stack)?.get_primitive_or::<Text>(
+                "Expected the URL of a Git repository",
+                env,
+                stack,
+            )?;
+
+            Ok(Value::of(Dependency {
+                source: DependencySource::Git(url.text),
+            }))
+        })),
+    );
+}
+
+/// Collect the dependencies declared in a project file's 'dependencies'
+/// module, sorted by name.
+pub fn get_dependencies(
+    project_module: &Module,
+    env: &EnvironmentRef,
+    stack: &Stack,
+) -> Result<Vec<(String, Dependency)>> {
+    let stack = stack.add(|| String::from("Resolving dependencies in project"));
+
+    let value =
+        match Name::new("dependencies").resolve_without_computing_if_present(&project_module.env) {
+            Some(value) => value,
+            None => return Ok(Vec::new()),
+        };
+
+    let module = value.evaluate(env, &stack)?.get_primitive_or::<Module>(
+        "Expected a module containing the project's dependencies",
+        env,
+        &stack,
+    )?;
+
+    let variables = module.env.borrow_mut().variables().clone();
+
+    let mut dependencies = variables
+        .into_iter()
+        .map(|(name, value)| {
+            let dependency = value.get_primitive_or::<Dependency>(
+                &format!(
+                    "Expected '{}' to be a dependency declared using 'path' or 'git'",
+                    name
+                ),
+                env,
+                &stack,
+            )?;
+
+            Ok((name, dependency))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    dependencies.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    Ok(dependencies)
+}
+
+/// Fetch each dependency into the project's dependency folder and make them
+/// available to 'resolve'. The contents of Git dependencies are checked
+/// against the project's lockfile, and new dependencies are added to it. Local
+/// dependencies are recorded without a hash and aren't checked, since they're
+/// expected to change.
+pub fn install_dependencies(
+    project_root: &Path,
+    dependencies: &[(String, Dependency)],
+    stack: &Stack,
+) -> Result<()> {
+    let lockfile_error = |action: &str, error: io::Error| {
+        ReturnState::Error(Box::new(
+            Error::new(
+                &format!("Error {} {}: {}", action, LOCKFILE_NAME, error),
+                stack,
+            )
+            .with_code(ErrorCode::ImportFailed),
+        ))
+    };
+
+    let lockfile =
+        Lockfile::read(project_root).map_err(|error| lockfile_error("reading", error))?;
+    let mut new_lockfile = Lockfile::default();
+    let mut installed = HashMap::new();
+
+    for (name, dependency) in dependencies {
+        let stack = stack.add(|| format!("Installing dependency '{}'", name));
+
+        if !is_valid_dependency_name(name) {
+            return Err(ReturnState::Error(Box::new(
+                Error::new(&format!("Invalid dependency name '{}'", name), &stack)
+                    .with_code(ErrorCode::ImportFailed)
+                    .with_help("Dependency names can't contain '/' or refer to other folders"),
+            )));
+        }
+
+        // Only use the locked version if the dependency still comes from the
+        // same place
+        let locked = lockfile
+            .dependencies
+            .get(name)
+            .filter(|locked| locked.source == dependency.source);
+
+        let destination = project_root.join(DEPENDENCIES_FOLDER).join(name);
+
+        let installed_dependency = install_dependency(
+            project_root,
+            dependency,
+            locked.map(|locked| locked.version.as_str()),
+            &destination,
+        )
+        .map_err(|error| {
+            ReturnState::Error(Box::new(
+                Error::new(
+                    &format!("Error installing dependency '{}': {}", name, error),
+                    &stack,
+                )
+                .with_code(ErrorCode::ImportFailed),
+            ))
+        })?;
+
+        // Local dependencies don't have a hash, so only dependencies fetched
+        // from elsewhere are checked
+        let hashes = (
+            locked.and_then(|locked| locked.hash.as_ref()),
+            &installed_dependency.hash,
+        );
+
+        if let (Some(locked_hash), Some(hash)) = hashes {
+            if locked_hash != hash {
+                return Err(ReturnState::Error(Box::new(
+                    Error::new(
+                        &format!(
+                            "Dependency '{}' does not match {} (expected hash {}, found {})",
+                            name, LOCKFILE_NAME, locked_hash, hash
+                        ),
+                        &stack,
+                    )
+                    .with_code(ErrorCode::ImportFailed)
+                    .with_help(&format!(
+                        "Remove its entry from {} to accept the new contents",
+                        LOCKFILE_NAME
+                    )),
+                )));
+            }
+        }
+
+        new_lockfile
+            .dependencies
+            .insert(name.clone(), installed_dependency);
+
+        installed.insert(name.clone(), destination);
+    }
+
+    if new_lockfile != lockfile {
+        new_lockfile
+            .write(project_root)
+            .map_err(|error| lockfile_error("writing", error))?;
+    }
+
+    INSTALLED_DEPENDENCIES.with(|dependencies| {
+        dependencies
+            .borrow_mut()
+            .insert(project_root.to_path_buf(), installed)
+    });
+
+    Ok(())
+}
+
+/// Make dependencies installed by an earlier call to 'install_dependencies'
+/// available to 'resolve' without fetching anything or changing the lockfile.
+/// Fails if a dependency isn't installed or no longer matches the lockfile.
+pub fn use_installed_dependencies(
+    project_root: &Path,
+    dependencies: &[(String, Dependency)],
+    stack: &Stack,
+) -> Result<()> {
+    let lockfile = Lockfile::read(project_root).map_err(|error| {
+        ReturnState::Error(Box::new(
+            Error::new(
+                &format!("Error reading {}: {}", LOCKFILE_NAME, error),
+                stack,
+            )
+            .with_code(ErrorCode::ImportFailed),
+        ))
+    })?;
+
+    let mut installed = HashMap::new();
+
+    for (name, dependency) in dependencies {
+        let stack = stack.add(|| format!("Using installed dependency '{}'", name));
+
+        let destination = project_root.join(DEPENDENCIES_FOLDER).join(name);
+
+        let locked = lockfile
+            .dependencies
+            .get(name)
+            .filter(|locked| locked.source == dependency.source);
+
+        let is_installed = is_valid_dependency_name(name)
+            && destination.is_dir()
+            && match locked.map(|locked| &locked.hash) {
+                Some(Some(hash)) => hash_folder(&destination).ok().as_ref() == Some(hash),
+                Some(None) => true,
+                None => false,
+            };
+
+        if !is_installed {
+            return Err(ReturnState::Error(Box::new(
+                Error::new(&format!("Dependency '{}' is not installed", name), &stack)
+                    .with_code(ErrorCode::ImportFailed)
+                    .with_help("Run the project to install its dependencies"),
+            )));
+        }
+
+        installed.insert(name.clone(), destination);
+    }
+
+    INSTALLED_DEPENDENCIES.with(|dependencies| {
+        dependencies
+            .borrow_mut()
+            .insert(project_root.to_path_buf(), installed)
+    });
+
+    Ok(())
+}
+
+/// Dependencies are installed into a folder named after the dependency, so
+/// the name must be a single folder name. Otherwise installing a dependency
+/// could replace folders outside the dependencies folder.
+fn is_valid_dependency_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) && !name.contains(['/', '\\'])
+}
+
+fn install_dependency(
+    project_root: &Path,
+    dependency: &Dependency,
+    locked_version: Option<&str>,
+    destination: &Path,
+) -> io::Result<LockedDependency> {
+    let (version, hash) = match &dependency.source {
+        DependencySource::Path(path) => {
+            let source = project_root.join(path);
+
+            if !source.is_dir() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{} is not a folder", source.to_string_lossy()),
+                ));
+            }
+
+            // Local dependencies are copied every time so changes to them are
+            // picked up
+            if destination.exists() {
+                fs::remove_dir_all(destination)?;
+            }
+
+            copy_folder(&source, destination)?;
+
+            (String::from("local"), None)
+        }
+        DependencySource::Git(url) => {
+            // Git would read a URL starting with '-' as an option
+            if url.starts_with('-') {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("'{}' is not a valid Git URL", url),
+                ));
+            }
+
+            if let Some(version) = locked_version {
+                if !is_commit_hash(version) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "version '{}' in {} is not a commit hash",
+                            version, LOCKFILE_NAME
+                        ),
+                    ));
+                }
+            }
+
+            // Only reuse a folder that was cloned from the same URL, so a
+            // dependency whose source changed (or a folder left behind by a
+            // local dependency) is cloned again
+            if destination.exists() && !is_clone_of(destination, url) {
+                fs::remove_dir_all(destination)?;
+            }
+
+            if !destination.exists() {
+                if let Some(parent) = destination.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+
+                git(
+                    &[
+                        "clone",
+                        "--quiet",
+                        "--",
+                        url,
+                        &destination.to_string_lossy(),
+                    ],
+                    None,
+                )?;
+            }
+
+            if let Some(version) = locked_version {
+                git(&["checkout", "--quiet", version], Some(destination))?;
+            }
+
+            (
+                git(&["rev-parse", "HEAD"], Some(destination))?,
+                Some(hash_folder(destination)?),
+            )
+        }
+    };
+
+    Ok(LockedDependency {
+        source: dependency.source.clone(),
+        version,
+        hash,
+    })
+}
+
+/// Whether the version is a full commit hash, like the ones written to the
+/// lockfile.
+fn is_commit_hash(version: &str) -> bool {
+    version.len() == 40 && version.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Whether the folder is a Git checkout cloned from the URL.
+fn is_clone_of(folder: &Path, url: &str) -> bool {
+    folder.join(".git").is_dir()
+        && git(&["config", "--get", "remote.origin.url"], Some(folder))
+            .map(|remote| remote == url)
+            .unwrap_or(false)
+}
+
+/// Run a Git command, returning its output. Commands run in a folder use the
+/// repository in that folder, and never one the folder is inside of.
+fn git(args: &[&str], folder: Option<&Path>) -> io::Result<String> {
+    let mut command = Command::new("git");
+
+    if let Some(folder) = folder {
+        command
+            .arg("--git-dir")
+            .arg(folder.join(".git"))
+            .arg("--work-tree")
+            .arg(folder)
+            .current_dir(folder);
+    }
+
+    let output = command.args(args).output()?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(io::Error::other(format!(
+            "'git {}' failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+fn copy_folder(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+
+    for entry in from.read_dir()? {
+        let path = entry?.path();
+        let file_name = path.file_name().unwrap();
+
+        // Skip hidden files, including other projects' installed dependencies
+        if file_name.to_string_lossy().starts_with('.') {
+            continue;
+        }
+
+        if path.is_dir() {
+            copy_folder(&path, &to.join(file_name))?;
+        } else {
+            fs::copy(&path, to.join(file_name))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Find where a dependency of the project at 'project_root' was installed.
+pub fn installed_dependency(project_root: &Path, name: &str) -> Option<PathBuf> {
+    INSTALLED_DEPENDENCIES.with(|dependencies| {
+        dependencies
+            .borrow()
+            .get(project_root)
+            .and_then(|dependencies| dependencies.get(name))
+            .cloned()
+    })
+}
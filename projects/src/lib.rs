@@ -1,14 +1,22 @@
+mod cache;
+mod dependencies;
 mod load;
+mod lock;
 mod project;
 mod resolve;
 
+pub use cache::*;
+pub use dependencies::*;
 pub use load::*;
+pub use lock::*;
 pub use project::*;
 pub use resolve::*;
 
 use wipple::*;
 
 pub fn setup() {
+    clear_module_cache();
+
     let env = Environment::global();
 
     env.borrow_mut().set_variable(
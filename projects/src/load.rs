@@ -14,15 +14,18 @@ pub fn include(module_name: &str, env: &EnvironmentRef, stack: &Stack) -> Result
     import_path_with_parent_env(&path, env, &stack)
 }
 
-/// Import a file/folder using a path
+/// Import a file/folder using a path. Importing the same path again returns
+/// the same module without evaluating it again.
 pub fn import_path(path: &Path, stack: &Stack) -> Result<Module> {
-    let stack = stack.add(|| format!("Importing {}", path.to_string_lossy()));
+    let stack = add_import(path, stack)?;
 
-    if let Some(module) = try_import_folder(path, &stack)? {
-        Ok(module)
-    } else {
-        import_file(path, &stack)
-    }
+    cached_module(path, || {
+        if let Some(module) = try_import_folder(path, &stack)? {
+            Ok(module)
+        } else {
+            import_file(path, &stack)
+        }
+    })
 }
 
 pub fn import_path_with_parent_env(
@@ -30,7 +33,7 @@ pub fn import_path_with_parent_env(
     env: &EnvironmentRef,
     stack: &Stack,
 ) -> Result<Module> {
-    let stack = stack.add(|| format!("Importing {}", path.to_string_lossy()));
+    let stack = add_import(path, stack)?;
 
     if let Some(module) = try_import_folder(path, &stack)? {
         Ok(module)
@@ -39,6 +42,59 @@ pub fn import_path_with_parent_env(
     }
 }
 
+/// Add an item for importing the path to the stack, failing if the path is
+/// already being imported further up the stack.
+fn add_import(path: &Path, stack: &Stack) -> Result<Stack> {
+    check_for_circular_import(path, stack)?;
+
+    let mut stack = stack.add(|| format!("Importing {}", path.to_string_lossy()));
+
+    if let Ok(path) = path.canonicalize() {
+        stack.imports.push(path);
+    }
+
+    Ok(stack)
+}
+
+/// Fail if the path is already being imported further up the stack, listing
+/// every import in the cycle.
+fn check_for_circular_import(path: &Path, stack: &Stack) -> Result<()> {
+    let path = match path.canonicalize() {
+        Ok(path) => path,
+        Err(_) => return Ok(()),
+    };
+
+    let start = match stack.imports.iter().position(|import| *import == path) {
+        Some(start) => start,
+        None => return Ok(()),
+    };
+
+    // Show paths relative to the project (or the current folder) when possible
+    let base = stack
+        .project_root
+        .clone()
+        .or_else(|| std::env::current_dir().ok())
+        .and_then(|root| root.canonicalize().ok());
+
+    let cycle = stack.imports[start..]
+        .iter()
+        .chain(std::iter::once(&path))
+        .map(|import| {
+            base.as_ref()
+                .and_then(|root| import.strip_prefix(root).ok())
+                .unwrap_or(import)
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect::<Vec<_>>()
+        .join(" -> ");
+
+    Err(ReturnState::Error(Box::new(
+        Error::new(&format!("Circular import: {}", cycle), stack)
+            .with_code(ErrorCode::ImportFailed),
+    )))
+}
+
 fn try_import_folder(path: &Path, stack: &Stack) -> Result<Option<Module>> {
     if !path.is_dir() {
         return Ok(None);
@@ -61,18 +117,28 @@ fn try_import_folder(path: &Path, stack: &Stack) -> Result<Option<Module>> {
         let mut files = path
             .read_dir()
             .map_err(|error| {
-                ReturnState::Error(Error::new(
-                    &format!("Error reading folder {}: {}", path.to_string_lossy(), error),
-                    &stack,
+                ReturnState::Error(Box::new(
+                    Error::new(
+                        &format!("Error reading folder {}: {}", path.to_string_lossy(), error),
+                        &stack,
+                    )
+                    .with_code(ErrorCode::ImportFailed),
                 ))
             })?
             .filter_map(|entry| {
                 let path = match entry {
                     Ok(file) => file.path(),
                     Err(error) => {
-                        return Some(Err(ReturnState::Error(Error::new(
-                            &format!("Error reading file {}: {}", path.to_string_lossy(), error),
-                            &stack,
+                        return Some(Err(ReturnState::Error(Box::new(
+                            Error::new(
+                                &format!(
+                                    "Error reading file {}: {}",
+                                    path.to_string_lossy(),
+                                    error
+                                ),
+                                &stack,
+                            )
+                            .with_code(ErrorCode::ImportFailed),
                         ))))
                     }
                 };
@@ -94,7 +160,7 @@ fn try_import_folder(path: &Path, stack: &Stack) -> Result<Option<Module>> {
         files.sort();
 
         for file in files {
-            let module = import_file(&file, &stack)?;
+            let module = import_path(&file, &stack)?;
             temp_env.borrow_mut().r#use(&module.env.borrow());
         }
 
@@ -128,22 +194,26 @@ pub fn load_file_with_parent_env(
 /// Load a Wipple file into a value. Does not evaluate the file.
 pub fn load_file(path: &Path, stack: &Stack) -> Result {
     let code = fs::read_to_string(path).map_err(|error| {
-        ReturnState::Error(Error::new(
-            &format!("Error loading file {}: {}", path.to_string_lossy(), error),
-            stack,
+        ReturnState::Error(Box::new(
+            Error::new(
+                &format!("Error loading file {}: {}", path.to_string_lossy(), error),
+                stack,
+            )
+            .with_code(ErrorCode::ImportFailed),
         ))
     })?;
 
-    let ast = wipple_parser::parse_file(&code).map_err(|error| {
-        ReturnState::Error(Error::new(
-            &format!(
-                "Error parsing file {}: {}",
-                path.to_string_lossy(),
-                error.message
-            ),
-            stack,
-        ))
-    })?;
+    let (ast, errors) = wipple_parser::parse_file_recovering(&code);
+
+    if !errors.is_empty() {
+        return Err(ReturnState::Error(Box::new(
+            Error::new(
+                &wipple_parser::render_errors(&errors, &code, Some(path)),
+                stack,
+            )
+            .with_code(ErrorCode::SyntaxError),
+        )));
+    }
 
     let program = wipple_parser::convert(&ast, Some(path));
 
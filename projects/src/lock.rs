@@ -0,0 +1,196 @@
+use crate::*;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The name of the lockfile, stored next to 'project.wpl'.
+pub const LOCKFILE_NAME: &str = "project.lock";
+
+const LOCKFILE_HEADER: &str = "# This file is generated by Wipple; do not edit it manually";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedDependency {
+    pub source: DependencySource,
+    pub version: String,
+
+    /// The hash of the dependency's files, checked whenever it's installed
+    /// again. Local dependencies are expected to change, so they aren't
+    /// checked and don't have a hash.
+    pub hash: Option<String>,
+}
+
+/// The resolved dependencies of a project, keyed by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Lockfile {
+    pub dependencies: BTreeMap<String, LockedDependency>,
+}
+
+impl Lockfile {
+    /// Read the lockfile in the project root, or an empty lockfile if the
+    /// project doesn't have one yet.
+    pub fn read(project_root: &Path) -> io::Result<Self> {
+        let path = project_root.join(LOCKFILE_NAME);
+
+        if !path.exists() {
+            return Ok(Lockfile::default());
+        }
+
+        let contents = fs::read_to_string(&path)?;
+
+        Lockfile::parse(&contents).map_err(|message| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.to_string_lossy(), message),
+            )
+        })
+    }
+
+    pub fn write(&self, project_root: &Path) -> io::Result<()> {
+        fs::write(project_root.join(LOCKFILE_NAME), self.to_string())
+    }
+
+    pub fn parse(contents: &str) -> std::result::Result<Self, String> {
+        let mut lockfile = Lockfile::default();
+        let mut current: Option<(String, BTreeMap<String, String>)> = None;
+
+        fn finish(
+            lockfile: &mut Lockfile,
+            entry: Option<(String, BTreeMap<String, String>)>,
+        ) -> std::result::Result<(), String> {
+            let (name, mut fields) = match entry {
+                Some(entry) => entry,
+                None => return Ok(()),
+            };
+
+            let mut field = |key: &str| {
+                fields
+                    .remove(key)
+                    .ok_or_else(|| format!("Dependency '{}' is missing '{}'", name, key))
+            };
+
+            let source = field("source")?;
+            let source = match source.split_once(' ') {
+                Some(("path", path)) => DependencySource::Path(PathBuf::from(path)),
+                Some(("git", url)) => DependencySource::Git(String::from(url)),
+                _ => return Err(format!("Invalid source for dependency '{}'", name)),
+            };
+
+            let version = field("version")?;
+
+            let hash = match source {
+                DependencySource::Path(_) => None,
+                DependencySource::Git(_) => Some(field("hash")?),
+            };
+
+            let dependency = LockedDependency {
+                source,
+                version,
+                hash,
+            };
+
+            lockfile.dependencies.insert(name, dependency);
+
+            Ok(())
+        }
+
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                finish(&mut lockfile, current.take())?;
+                current = Some((String::from(name), BTreeMap::new()));
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once(" = ")
+                .ok_or_else(|| format!("Invalid line {}", index + 1))?;
+
+            current
+                .as_mut()
+                .ok_or_else(|| format!("Line {} is outside a dependency", index + 1))?
+                .1
+                .insert(String::from(key), String::from(value));
+        }
+
+        finish(&mut lockfile, current)?;
+
+        Ok(lockfile)
+    }
+}
+
+impl fmt::Display for Lockfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", LOCKFILE_HEADER)?;
+
+        for (name, dependency) in &self.dependencies {
+            writeln!(f)?;
+            writeln!(f, "[{}]", name)?;
+            writeln!(f, "source = {}", dependency.source)?;
+            writeln!(f, "version = {}", dependency.version)?;
+
+            if let Some(hash) = &dependency.hash {
+                writeln!(f, "hash = {}", hash)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for DependencySource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DependencySource::Path(path) => write!(f, "path {}", path.to_string_lossy()),
+            DependencySource::Git(url) => write!(f, "git {}", url),
+        }
+    }
+}
+
+/// Hash the contents of an installed dependency. Hidden files (eg. '.git') are
+/// ignored so the hash only depends on the files Wipple can import.
+pub fn hash_folder(folder: &Path) -> io::Result<String> {
+    fn collect(folder: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+        for entry in folder.read_dir()? {
+            let path = entry?.path();
+
+            if path.file_name().unwrap().to_string_lossy().starts_with('.') {
+                continue;
+            }
+
+            if path.is_dir() {
+                collect(&path, files)?;
+            } else {
+                files.push(path);
+            }
+        }
+
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    collect(folder, &mut files)?;
+    files.sort();
+
+    let mut hasher = Sha256::new();
+
+    for file in files {
+        let relative_path = file.strip_prefix(folder).unwrap();
+
+        // Separate the path from the contents so moving bytes between them
+        // changes the hash
+        hasher.update(relative_path.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(fs::read(&file)?);
+        hasher.update([0]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
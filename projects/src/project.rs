@@ -7,12 +7,19 @@ pub fn load_project(path: &Path, stack: &Stack) -> Result<Module> {
     setup_project_file(&mut env);
     let env = env.into_ref();
 
+    let project_root = path.parent().unwrap().to_path_buf();
+
     let mut stack = stack.add(|| format!("Importing project {}", path.to_string_lossy()));
-    stack.project_root = Some(path.parent().unwrap().to_path_buf());
+    stack.project_root = Some(project_root.clone());
 
     let project_module = load_file_with_parent_env(path, &env, &stack)?;
 
-    // TODO: Install dependencies
+    let dependencies = get_dependencies(&project_module, &env, &stack)?;
+    if stack.can_install_dependencies {
+        install_dependencies(&project_root, &dependencies, &stack)?;
+    } else {
+        use_installed_dependencies(&project_root, &dependencies, &stack)?;
+    }
 
     let main_file = get_main_file(&project_module, &env, &stack)?;
     let main_module = import(&main_file, &stack)?;
@@ -20,8 +27,8 @@ pub fn load_project(path: &Path, stack: &Stack) -> Result<Module> {
     Ok(main_module)
 }
 
-fn setup_project_file(_: &mut Environment) {
-    // TODO
+fn setup_project_file(env: &mut Environment) {
+    setup_dependencies(env);
 }
 
 fn get_main_file(project_module: &Module, env: &EnvironmentRef, stack: &Stack) -> Result<String> {
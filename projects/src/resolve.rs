@@ -1,21 +1,30 @@
-use std::path::PathBuf;
+use crate::*;
+use std::path::{Component, Path, PathBuf};
 use wipple::*;
 
 /// Resolve a module name into a path.
 pub fn resolve(module_name: &str, stack: &Stack) -> Result<PathBuf> {
-    let base = if module_name.starts_with("./") || module_name.starts_with("../") {
-        stack
-            .current_file
-            .clone()
-            .ok_or_else(|| ReturnState::Error(Error::new("Current file is not set", stack)))
+    let path = if module_name.starts_with("./") || module_name.starts_with("../") {
+        let current_file = stack.current_file.clone().ok_or_else(|| {
+            ReturnState::Error(Box::new(
+                Error::new("Current file is not set", stack).with_code(ErrorCode::ImportFailed),
+            ))
+        })?;
+
+        current_file
+            .parent()
+            .map(|folder| folder.join(module_name))
+            .unwrap_or_else(|| PathBuf::from(module_name))
     } else {
-        stack
-            .project_root
-            .clone()
-            .ok_or_else(|| ReturnState::Error(Error::new("Project root is not set", stack)))
-    }?;
+        let project_root = stack.project_root.clone().ok_or_else(|| {
+            ReturnState::Error(Box::new(
+                Error::new("Project root is not set", stack).with_code(ErrorCode::ImportFailed),
+            ))
+        })?;
 
-    let path = base.join(module_name);
+        resolve_dependency(&project_root, module_name)
+            .unwrap_or_else(|| project_root.join(module_name))
+    };
 
     let path = if path.is_dir() {
         path
@@ -25,3 +34,18 @@ pub fn resolve(module_name: &str, stack: &Stack) -> Result<PathBuf> {
 
     Ok(path)
 }
+
+/// Resolve a module name whose first component is the name of an installed
+/// dependency, eg. 'utils' or 'utils/strings'.
+fn resolve_dependency(project_root: &Path, module_name: &str) -> Option<PathBuf> {
+    let mut components = Path::new(module_name).components();
+
+    let name = match components.next()? {
+        Component::Normal(name) => name.to_str()?,
+        _ => return None,
+    };
+
+    let dependency = installed_dependency(project_root, name)?;
+
+    Some(dependency.join(components.as_path()))
+}
@@ -0,0 +1,349 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use wipple::*;
+use wipple_projects::*;
+
+fn project_folder(name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!(
+        "wipple-projects-test-{}-{}",
+        name,
+        std::process::id()
+    ));
+
+    if folder.exists() {
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    fs::create_dir_all(&folder).unwrap();
+
+    folder
+}
+
+fn git(folder: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(folder)
+        .output()
+        .unwrap()
+        .status;
+
+    assert!(status.success(), "'git {}' failed", args.join(" "));
+}
+
+fn install(project_root: &Path, name: &str, source: DependencySource) -> wipple::Result<()> {
+    let dependencies = vec![(String::from(name), Dependency { source })];
+    install_dependencies(project_root, &dependencies, &Stack::new())
+}
+
+/// Create a Git repository with a single file and commit, returning its path.
+fn repository(folder: &Path, name: &str, contents: &str) -> PathBuf {
+    let repository = folder.join(name);
+
+    fs::create_dir_all(&repository).unwrap();
+    fs::write(repository.join("strings.wpl"), contents).unwrap();
+    git(&repository, &["init", "--quiet"]);
+    git(&repository, &["add", "."]);
+    git(&repository, &["commit", "--quiet", "-m", "Initial commit"]);
+
+    repository
+}
+
+fn head(repository: &Path) -> String {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(repository)
+        .output()
+        .unwrap();
+
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+#[test]
+fn dependency_names_must_be_folder_names() {
+    let folder = project_folder("names");
+    let project_root = folder.join("project");
+
+    fs::create_dir_all(project_root.join("utils")).unwrap();
+    fs::write(project_root.join("utils/utils.wpl"), "x : 1\n").unwrap();
+
+    // A folder outside the project that a malicious name could point to
+    fs::create_dir_all(folder.join("important")).unwrap();
+    fs::write(folder.join("important/file"), "keep me").unwrap();
+
+    for name in ["../../../important", "a/b", "..", "/tmp", ""] {
+        let error = install(
+            &project_root,
+            name,
+            DependencySource::Path(PathBuf::from("utils")),
+        )
+        .err()
+        .unwrap_or_else(|| panic!("'{}' should be rejected", name))
+        .into_error(&Stack::new());
+
+        assert_eq!(error.message, format!("Invalid dependency name '{}'", name));
+    }
+
+    assert_eq!(
+        fs::read_to_string(folder.join("important/file")).unwrap(),
+        "keep me"
+    );
+
+    install(
+        &project_root,
+        "utils",
+        DependencySource::Path(PathBuf::from("utils")),
+    )
+    .unwrap_or_else(|state| panic!("{}", state.into_error(&Stack::new())));
+
+    assert!(project_root
+        .join(DEPENDENCIES_FOLDER)
+        .join("utils/utils.wpl")
+        .exists());
+
+    fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn changed_git_dependencies_do_not_match_the_lockfile() {
+    let folder = project_folder("git");
+    let project_root = folder.join("project");
+    let repository = repository(&folder, "strings", "x : 1\n");
+
+    fs::create_dir_all(&project_root).unwrap();
+
+    let source = DependencySource::Git(repository.to_string_lossy().to_string());
+
+    install(&project_root, "strings", source.clone())
+        .unwrap_or_else(|state| panic!("{}", state.into_error(&Stack::new())));
+
+    let lockfile = Lockfile::read(&project_root).unwrap();
+    assert_eq!(lockfile.dependencies["strings"].source, source);
+
+    // Change the installed copy so it no longer matches the locked hash
+    let installed = project_root.join(DEPENDENCIES_FOLDER).join("strings");
+    fs::write(installed.join("strings.wpl"), "x : 2\n").unwrap();
+
+    let error = install(&project_root, "strings", source)
+        .unwrap_err()
+        .into_error(&Stack::new());
+
+    assert!(
+        error
+            .message
+            .starts_with("Dependency 'strings' does not match project.lock"),
+        "{}",
+        error.message
+    );
+
+    assert_eq!(
+        error.help.as_deref(),
+        Some("Remove its entry from project.lock to accept the new contents")
+    );
+
+    fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn changed_local_dependencies_are_installed_again() {
+    let folder = project_folder("local");
+    let project_root = folder.join("project");
+
+    fs::create_dir_all(project_root.join("utils")).unwrap();
+    fs::write(project_root.join("utils/utils.wpl"), "x : 1\n").unwrap();
+
+    let source = DependencySource::Path(PathBuf::from("utils"));
+
+    install(&project_root, "utils", source.clone())
+        .unwrap_or_else(|state| panic!("{}", state.into_error(&Stack::new())));
+
+    fs::write(project_root.join("utils/utils.wpl"), "x : 2\n").unwrap();
+
+    install(&project_root, "utils", source)
+        .unwrap_or_else(|state| panic!("{}", state.into_error(&Stack::new())));
+
+    let installed = project_root
+        .join(DEPENDENCIES_FOLDER)
+        .join("utils/utils.wpl");
+    assert_eq!(fs::read_to_string(installed).unwrap(), "x : 2\n");
+
+    // Local dependencies aren't checked, so no hash is recorded for them
+    let lockfile = Lockfile::read(&project_root).unwrap();
+    assert_eq!(lockfile.dependencies["utils"].hash, None);
+
+    fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn git_urls_starting_with_a_dash_are_rejected() {
+    let folder = project_folder("git-options");
+    let project_root = folder.join("project");
+    let marker = folder.join("marker");
+
+    fs::create_dir_all(&project_root).unwrap();
+
+    let url = format!("--upload-pack=touch {}", marker.to_string_lossy());
+
+    let error = install(&project_root, "strings", DependencySource::Git(url.clone()))
+        .unwrap_err()
+        .into_error(&Stack::new());
+
+    assert_eq!(
+        error.message,
+        format!(
+            "Error installing dependency 'strings': '{}' is not a valid Git URL",
+            url
+        )
+    );
+
+    assert!(!marker.exists());
+
+    fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn locked_git_versions_must_be_commit_hashes() {
+    let folder = project_folder("git-versions");
+    let project_root = folder.join("project");
+    let repository = repository(&folder, "strings", "x : 1\n");
+
+    fs::create_dir_all(&project_root).unwrap();
+
+    let source = DependencySource::Git(repository.to_string_lossy().to_string());
+
+    install(&project_root, "strings", source.clone())
+        .unwrap_or_else(|state| panic!("{}", state.into_error(&Stack::new())));
+
+    let mut lockfile = Lockfile::read(&project_root).unwrap();
+    assert_eq!(lockfile.dependencies["strings"].version, head(&repository));
+
+    lockfile.dependencies.get_mut("strings").unwrap().version = String::from("--orphan=x");
+    lockfile.write(&project_root).unwrap();
+
+    let error = install(&project_root, "strings", source)
+        .unwrap_err()
+        .into_error(&Stack::new());
+
+    assert_eq!(
+        error.message,
+        "Error installing dependency 'strings': version '--orphan=x' in project.lock is not a commit hash"
+    );
+
+    fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn git_dependencies_are_cloned_again_when_their_source_changes() {
+    let folder = project_folder("git-sources");
+    let project_root = folder.join("project");
+    let first = repository(&folder, "first", "x : 1\n");
+    let second = repository(&folder, "second", "x : 2\n");
+
+    fs::create_dir_all(&project_root).unwrap();
+
+    let installed = project_root
+        .join(DEPENDENCIES_FOLDER)
+        .join("strings/strings.wpl");
+
+    for (repository, contents) in [(&first, "x : 1\n"), (&second, "x : 2\n")] {
+        let source = DependencySource::Git(repository.to_string_lossy().to_string());
+
+        install(&project_root, "strings", source)
+            .unwrap_or_else(|state| panic!("{}", state.into_error(&Stack::new())));
+
+        assert_eq!(fs::read_to_string(&installed).unwrap(), contents);
+
+        let lockfile = Lockfile::read(&project_root).unwrap();
+        assert_eq!(lockfile.dependencies["strings"].version, head(repository));
+    }
+
+    fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn local_dependencies_are_replaced_when_they_become_git_dependencies() {
+    let folder = project_folder("local-to-git");
+    let project_root = repository(&folder, "project", "x : 0\n");
+    let repository = repository(&folder, "strings", "x : 2\n");
+
+    fs::create_dir_all(project_root.join("strings")).unwrap();
+    fs::write(project_root.join("strings/strings.wpl"), "x : 1\n").unwrap();
+
+    install(
+        &project_root,
+        "strings",
+        DependencySource::Path(PathBuf::from("strings")),
+    )
+    .unwrap_or_else(|state| panic!("{}", state.into_error(&Stack::new())));
+
+    // The folder left behind by the local dependency isn't a Git checkout, so
+    // Git must not use the project's own repository instead
+    install(
+        &project_root,
+        "strings",
+        DependencySource::Git(repository.to_string_lossy().to_string()),
+    )
+    .unwrap_or_else(|state| panic!("{}", state.into_error(&Stack::new())));
+
+    let installed = project_root
+        .join(DEPENDENCIES_FOLDER)
+        .join("strings/strings.wpl");
+    assert_eq!(fs::read_to_string(installed).unwrap(), "x : 2\n");
+
+    let lockfile = Lockfile::read(&project_root).unwrap();
+    assert_eq!(lockfile.dependencies["strings"].version, head(&repository));
+
+    fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn projects_only_use_installed_dependencies_when_installing_is_disabled() {
+    wipple::setup();
+    wipple_projects::setup();
+
+    let folder = project_folder("no-install");
+    let project_root = folder.join("project");
+
+    fs::create_dir_all(project_root.join("utils")).unwrap();
+    fs::write(project_root.join("utils/utils.wpl"), "x : 1\n").unwrap();
+    fs::write(
+        project_root.join("project.wpl"),
+        "main : \"main\"\ndependencies : {\n    utils : path \"utils\"\n}\n",
+    )
+    .unwrap();
+    fs::write(project_root.join("main.wpl"), "x : 2\n").unwrap();
+
+    let project_file = project_root.join("project.wpl");
+
+    let mut stack = Stack::new();
+    stack.can_install_dependencies = false;
+
+    let error = load_project(&project_file, &stack)
+        .err()
+        .expect("the dependency isn't installed yet")
+        .into_error(&Stack::new());
+
+    assert_eq!(error.message, "Dependency 'utils' is not installed");
+    assert!(!project_root.join(DEPENDENCIES_FOLDER).exists());
+    assert!(!project_root.join(LOCKFILE_NAME).exists());
+
+    load_project(&project_file, &Stack::new())
+        .unwrap_or_else(|state| panic!("{}", state.into_error(&Stack::new())));
+
+    // Changes to local dependencies are only picked up by installing them
+    // again
+    fs::write(project_root.join("utils/utils.wpl"), "x : 3\n").unwrap();
+
+    load_project(&project_file, &stack)
+        .unwrap_or_else(|state| panic!("{}", state.into_error(&Stack::new())));
+
+    let installed = project_root
+        .join(DEPENDENCIES_FOLDER)
+        .join("utils/utils.wpl");
+    assert_eq!(fs::read_to_string(installed).unwrap(), "x : 1\n");
+
+    fs::remove_dir_all(&folder).unwrap();
+}
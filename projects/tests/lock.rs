@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+use wipple_projects::*;
+
+const LOCKFILE: &str = "# This file is generated by Wipple; do not edit it manually
+
+[strings]
+source = git https://example.com/strings.git
+version = 0123456789abcdef
+hash = abc123
+
+[utils]
+source = path ../utils
+version = local
+";
+
+fn lockfile() -> Lockfile {
+    let mut lockfile = Lockfile::default();
+
+    lockfile.dependencies.insert(
+        String::from("strings"),
+        LockedDependency {
+            source: DependencySource::Git(String::from("https://example.com/strings.git")),
+            version: String::from("0123456789abcdef"),
+            hash: Some(String::from("abc123")),
+        },
+    );
+
+    lockfile.dependencies.insert(
+        String::from("utils"),
+        LockedDependency {
+            source: DependencySource::Path(PathBuf::from("../utils")),
+            version: String::from("local"),
+            hash: None,
+        },
+    );
+
+    lockfile
+}
+
+#[test]
+fn parse() {
+    assert_eq!(Lockfile::parse(LOCKFILE).unwrap(), lockfile());
+}
+
+#[test]
+fn parse_empty() {
+    assert_eq!(Lockfile::parse("").unwrap(), Lockfile::default());
+}
+
+#[test]
+fn local_dependencies_do_not_have_a_hash() {
+    // A hash written for a local dependency is ignored
+    let contents = "[utils]\nsource = path ../utils\nversion = local\nhash = def456\n";
+
+    let lockfile = Lockfile::parse(contents).unwrap();
+    assert_eq!(lockfile.dependencies["utils"].hash, None);
+    assert!(!lockfile.to_string().contains("hash"));
+}
+
+#[test]
+fn display_round_trip() {
+    let text = lockfile().to_string();
+
+    assert_eq!(text, LOCKFILE);
+    assert_eq!(Lockfile::parse(&text).unwrap(), lockfile());
+}
+
+#[test]
+fn parse_errors() {
+    let errors = [
+        (
+            "[strings]\nsource = git ../strings\nversion = 0123456789abcdef\n",
+            "Dependency 'strings' is missing 'hash'",
+        ),
+        (
+            "[utils]\nsource = svn ../utils\nversion = local\nhash = 1\n",
+            "Invalid source for dependency 'utils'",
+        ),
+        ("[utils]\nsource path\n", "Invalid line 2"),
+        ("source = path ../utils\n", "Line 1 is outside a dependency"),
+    ];
+
+    for (contents, error) in errors {
+        assert_eq!(Lockfile::parse(contents).unwrap_err(), error);
+    }
+}
@@ -19,6 +19,11 @@ macro_rules! println_interactive {
 }
 
 fn main() {
+    // Tests run on the same kind of stack as programs run with 'wipple run'
+    run_on_large_stack(run_tests)
+}
+
+fn run_tests() {
     let tests_folder = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests");
 
     let args = std::env::args().collect::<Vec<_>>();
@@ -48,7 +53,7 @@ fn main() {
                 }
             }
 
-            let (output, duration) = test(&test_case.code);
+            let (output, duration) = test(&test_case.code, &test_case.options);
 
             let reported_duration = Duration::from_std(duration).unwrap();
 
@@ -151,14 +156,21 @@ fn filter_lines(string: &str, filter: impl Fn(&&str) -> bool) -> String {
 
 struct Test {
     name: String,
+    options: Vec<String>,
     code: String,
     expected_output: String,
 }
 
+/// Lines starting with '#!' set options for the test they're in, eg.
+/// '#! max-steps 1000'. Other lines starting with '#' are comments.
+const OPTION_PREFIX: &str = "#!";
+
 fn parse_test_file(path: &Path) -> Vec<Test> {
     let file = fs::read_to_string(path).unwrap();
 
-    let file = filter_lines(&file, |line| !line.starts_with('#'));
+    let file = filter_lines(&file, |line| {
+        line.starts_with(OPTION_PREFIX) || !line.starts_with('#')
+    });
 
     file.split(">>>")
         .skip(1)
@@ -166,27 +178,75 @@ fn parse_test_file(path: &Path) -> Vec<Test> {
             let parts: Vec<_> = test_code.split("---").map(|x| x.trim()).collect();
             let first_part: Vec<_> = parts[0].splitn(2, '\n').map(|x| x.trim()).collect();
 
+            let options = first_part[1]
+                .lines()
+                .filter_map(|line| line.strip_prefix(OPTION_PREFIX))
+                .map(|option| String::from(option.trim()))
+                .collect();
+
+            let code = filter_lines(first_part[1], |line| !line.starts_with(OPTION_PREFIX));
+
             Test {
                 name: String::from(first_part[0]),
-                code: String::from(first_part[1]),
+                options,
+                code: String::from(code.trim()),
                 expected_output: String::from(parts[1]),
             }
         })
         .collect()
 }
 
-fn test(code: &str) -> (String, std::time::Duration) {
+/// Configure the stack a test runs with. Options are named after the options
+/// of 'wipple run'.
+fn apply_option(stack: &mut Stack, option: &str) {
+    let (name, value) = option
+        .split_once(' ')
+        .unwrap_or_else(|| panic!("expected a value for option '{}'", option));
+
+    let value = value
+        .trim()
+        .parse::<usize>()
+        .unwrap_or_else(|_| panic!("expected a number for option '{}'", name));
+
+    match name {
+        "max-depth" => stack.max_depth = value,
+        "max-steps" => stack.budget = stack.budget.clone().with_max_steps(value),
+        "max-allocated" => stack.budget = stack.budget.clone().with_max_allocated(value),
+        _ => panic!("unknown option '{}'", name),
+    }
+}
+
+/// Files that tests can import, eg. 'import "circular/a"'. Paths to these
+/// files are shown relative to this folder in the output.
+fn fixtures_folder() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures")
+}
+
+fn test(code: &str, options: &[String]) -> (String, std::time::Duration) {
     let start = Instant::now();
 
-    let stack = Stack::new();
+    let mut stack = Stack::new();
+    stack.project_root = Some(fixtures_folder());
+    for option in options {
+        apply_option(&mut stack, option);
+    }
+
+    let (ast, errors) = wipple_parser::parse_inline_program_recovering(code);
+
+    if !errors.is_empty() {
+        return (
+            wipple_parser::render_errors(&errors, code, None),
+            start.elapsed(),
+        );
+    }
 
-    let ast = wipple_parser::parse_inline_program(code).unwrap();
     let program = wipple_parser::convert(&ast, None);
 
     let output = Rc::new(RefCell::new(Vec::new()));
 
     let env = Environment::global();
     wipple::setup();
+    wipple_projects::setup();
     setup(output.clone(), &env);
 
     let env = Environment::child_of(&env).into_ref();
@@ -195,7 +255,11 @@ fn test(code: &str) -> (String, std::time::Duration) {
         output.replace(vec![error.into_error(&stack).to_string()]);
     }
 
-    let output = output.borrow().join("\n");
+    let output = output
+        .borrow()
+        .join("\n")
+        .replace(&format!("{}/", fixtures_folder().to_string_lossy()), "");
+
     let duration = start.elapsed();
 
     (output, duration)